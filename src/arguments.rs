@@ -0,0 +1,166 @@
+use std::{path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+/// Recover your wallet and vote on the proposals of the Jörmungandr blockchain
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(name = "jorvote", rename_all = "kebab-case")]
+pub struct Arguments {
+    /// configuration file with the vote plans and the network profiles, parsed
+    /// as JSON if it has the `.json` extension and as TOML otherwise
+    #[structopt(
+        long,
+        env = "JORVOTE_CONFIG",
+        default_value = "jorvote.toml",
+        parse(from_os_str)
+    )]
+    pub config: PathBuf,
+
+    /// name of the network profile to use
+    #[structopt(long, env = "JORVOTE_NETWORK")]
+    pub network: Option<String>,
+
+    /// base URL of the node REST API (e.g. `http://127.0.0.1:8443/api`)
+    #[structopt(long, env = "JORVOTE_REST_URL")]
+    pub rest_url: Option<String>,
+
+    /// URL the vote fragments are posted to, `{rest-url}/v0/message` by default
+    #[structopt(long, env = "JORVOTE_FRAGMENT_URL")]
+    pub fragment_url: Option<String>,
+
+    /// link to a fragment in a block explorer, `{}` is replaced by the fragment id
+    #[structopt(long, env = "JORVOTE_EXPLORER_URL")]
+    pub explorer_url: Option<String>,
+
+    /// how long to wait, in seconds, for a sent vote to be in a block
+    #[structopt(long, env = "JORVOTE_CONFIRMATION_TIMEOUT")]
+    pub confirmation_timeout: Option<u64>,
+
+    /// sign the votes with the fees and discrimination the node currently
+    /// applies rather than the block0's ones
+    #[structopt(long, env = "JORVOTE_LIVE_SETTINGS")]
+    pub live_settings: bool,
+
+    /// block0 file of the network
+    #[structopt(long, env = "JORVOTE_BLOCK0", parse(from_os_str))]
+    pub block0: Option<PathBuf>,
+
+    /// hash of the network's block0, the block0 is downloaded from the node if
+    /// no block0 file is given
+    #[structopt(long, env = "JORVOTE_GENESIS_HASH")]
+    pub genesis_hash: Option<String>,
+
+    /// encrypted keystore the wallet's secret can be saved to and unlocked
+    /// from, in the user's data directory by default
+    #[structopt(long, env = "JORVOTE_KEYSTORE", parse(from_os_str))]
+    pub keystore: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub enum Command {
+    /// run without the graphical interface
+    Cli(Cli),
+}
+
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub enum Cli {
+    /// list the proposals of the configuration with their choices
+    Proposals,
+    /// list the vote plans active on the node
+    VotePlans,
+    /// recover the wallet and print the ids of its accounts
+    Recover(Secret),
+    /// recover the wallet and print the state of its accounts on the node
+    AccountState(Secret),
+    /// recover the wallet and print the signed vote fragment
+    ///
+    /// The vote can be built offline by giving the value and the spending
+    /// counter of the account.
+    Vote {
+        #[structopt(flatten)]
+        secret: Secret,
+
+        /// position of the proposal in the configuration, as listed by the
+        /// `proposals` command
+        #[structopt(long)]
+        proposal: usize,
+
+        /// index of the chosen option
+        #[structopt(long)]
+        choice: u8,
+
+        /// position of the voting account in the wallet, as listed by the
+        /// `recover` command
+        #[structopt(long, default_value = "0")]
+        account: usize,
+
+        /// value of the account, the account state is not queried from the
+        /// node when given with the spending counter
+        #[structopt(long, requires = "counter")]
+        value: Option<u64>,
+
+        /// spending counter of the account
+        #[structopt(long, requires = "value")]
+        counter: Option<u32>,
+
+        /// send the vote to the node instead of printing it
+        #[structopt(long, conflicts_with = "output")]
+        submit: bool,
+
+        /// write the vote fragment to this file instead of printing it
+        #[structopt(long, parse(from_os_str))]
+        output: Option<PathBuf>,
+
+        /// format of the printed or written vote fragment: `hex` or `binary`
+        #[structopt(long, default_value = "hex")]
+        format: FragmentFormat,
+    },
+    /// send a vote fragment to the node and print its id
+    Submit {
+        /// the hex encoded fragment, read from the standard input if neither
+        /// the fragment nor an input file is given
+        fragment: Option<String>,
+
+        /// file with the fragment, hex encoded or binary
+        #[structopt(long, parse(from_os_str), conflicts_with = "fragment")]
+        input: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentFormat {
+    Hex,
+    Binary,
+}
+
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Secret {
+    /// mnemonics of the wallet or stake private key (bech32 or hex encoded),
+    /// read from the standard input if not given
+    #[structopt(long, env = "JORVOTE_MNEMONICS", hide_env_values = true)]
+    pub mnemonics: Option<String>,
+
+    /// passphrase of the mnemonics, if they are protected by one
+    #[structopt(long, env = "JORVOTE_PASSPHRASE", hide_env_values = true)]
+    pub passphrase: Option<String>,
+}
+
+impl FromStr for FragmentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(FragmentFormat::Hex),
+            "binary" => Ok(FragmentFormat::Binary),
+            _ => Err(format!(
+                "invalid fragment format '{}', expected hex or binary",
+                s
+            )),
+        }
+    }
+}
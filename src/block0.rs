@@ -0,0 +1,199 @@
+use chain_core::property::{Block as _, Deserialize as _};
+use chain_impl_mockchain::block::Block;
+use iced_futures::futures::{self, StreamExt as _};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Endpoints,
+    node_client::{self, Request},
+};
+
+/// where to find the block0 of the network
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Source {
+    /// block0 file on disk
+    pub path: Option<PathBuf>,
+    /// hash of the block0, used to check the file or to download the block0
+    /// from the node
+    pub genesis_hash: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NoSource,
+    InvalidHash(String),
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    Download(node_client::Error),
+    Invalid(std::io::Error),
+    HashMismatch {
+        expected: String,
+        found: String,
+    },
+}
+
+/// load the block0 in the background, the error being reported as a message
+pub fn query(
+    source: Source,
+    endpoints: Endpoints,
+    attempt: u32,
+) -> iced::Subscription<Result<Vec<u8>, String>> {
+    iced::Subscription::from_recipe(Load {
+        source,
+        endpoints,
+        attempt,
+    })
+}
+
+pub struct Load {
+    source: Source,
+    endpoints: Endpoints,
+    /// a new attempt loads the block0 again
+    attempt: u32,
+}
+
+// Make sure iced can use our loading stream
+impl<H, I> iced_native::subscription::Recipe<H, I> for Load
+where
+    H: std::hash::Hasher,
+{
+    type Output = Result<Vec<u8>, String>;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.source.hash(state);
+        self.endpoints.rest.hash(state);
+        self.attempt.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        let Load {
+            source, endpoints, ..
+        } = *self;
+
+        futures::stream::once(async move {
+            load(&source, &endpoints)
+                .await
+                .map_err(|error| error.to_string())
+        })
+        // We do not let the stream die, as it would load the block0 again
+        .chain(futures::stream::pending())
+        .boxed()
+    }
+}
+
+/// load the block0 from the file given in the source, from the local cache or
+/// from the node (in which case it is stored in the cache).
+///
+/// If a genesis hash is given the block0 is checked against it.
+pub async fn load(source: &Source, endpoints: &Endpoints) -> Result<Vec<u8>, Error> {
+    if let Some(path) = &source.path {
+        let block0 = read(path)?;
+        if let Some(hash) = &source.genesis_hash {
+            verify(&block0, hash)?;
+        }
+        return Ok(block0);
+    }
+
+    let hash = source.genesis_hash.as_ref().ok_or(Error::NoSource)?;
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::InvalidHash(hash.clone()));
+    }
+    let cached = cache_path(hash);
+
+    if let Some(path) = &cached {
+        if let Ok(block0) = fs::read(path) {
+            if verify(&block0, hash).is_ok() {
+                return Ok(block0);
+            }
+        }
+    }
+
+    let block0 = node_client::wait(node_client::fetch(Request::get(endpoints.block(hash))))
+        .await
+        .map_err(Error::Download)?;
+    verify(&block0, hash)?;
+
+    if let Some(path) = cached {
+        // the cache only saves a download, failing to write it is not an error
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(&path, &block0);
+    }
+
+    Ok(block0)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|error| Error::Io {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+/// check the block0 is a valid block of the given hash
+pub fn verify(block0: &[u8], hash: &str) -> Result<(), Error> {
+    let block = Block::deserialize(block0).map_err(Error::Invalid)?;
+    let found = block.id().to_string();
+
+    if found.eq_ignore_ascii_case(hash) {
+        Ok(())
+    } else {
+        Err(Error::HashMismatch {
+            expected: hash.to_owned(),
+            found,
+        })
+    }
+}
+
+fn cache_path(hash: &str) -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| {
+        dir.join("jorvote")
+            .join("block0")
+            .join(format!("{}.bin", hash.to_lowercase()))
+    })
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoSource => write!(
+                f,
+                "no block0 configured, a block0 file or a genesis hash is needed"
+            ),
+            Error::InvalidHash(hash) => write!(f, "invalid genesis hash '{}'", hash),
+            Error::Io { path, error } => {
+                write!(f, "cannot read block0 {}: {}", path.display(), error)
+            }
+            Error::Download(error) => write!(f, "cannot download block0: {}", error),
+            Error::Invalid(error) => write!(f, "invalid block0: {}", error),
+            Error::HashMismatch { expected, found } => write!(
+                f,
+                "block0 hash mismatch, expected {} but found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            Error::Download(error) => Some(error),
+            Error::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
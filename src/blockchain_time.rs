@@ -0,0 +1,333 @@
+use chain_core::property::Deserialize as _;
+use chain_impl_mockchain::{block::Block, config::ConfigParam, fragment::Fragment};
+use iced_futures::futures;
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::vote_plans::BlockDate;
+
+/// the time parameters of the blockchain, as set in its block0
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    /// seconds since the UNIX epoch of the first slot
+    block0_time: u64,
+    /// in seconds
+    slot_duration: u64,
+    slots_per_epoch: u32,
+    /// how far the node's clock is ahead of the local one, in milliseconds
+    /// and negative when it is behind
+    offset: i64,
+}
+
+/// the block dates a vote plan accepts votes in, the end being excluded
+#[derive(Debug, Clone, Copy)]
+pub struct VoteWindow {
+    pub start: BlockDate,
+    pub end: BlockDate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowStatus {
+    /// the votes are accepted after this delay
+    Upcoming(Duration),
+    /// the votes are accepted for this long
+    Open(Duration),
+    Closed,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Invalid(std::io::Error),
+    /// the block0 does not set this parameter
+    Missing(&'static str),
+    /// the block0 sets this parameter to zero
+    Zero(&'static str),
+}
+
+impl Clock {
+    /// read the time parameters from the initial fragment of the block0
+    pub fn from_block0(block0: &[u8]) -> Result<Self, Error> {
+        let block = Block::deserialize(block0).map_err(Error::Invalid)?;
+
+        let mut block0_time = None;
+        let mut slot_duration = None;
+        let mut slots_per_epoch = None;
+        for fragment in block.contents.iter() {
+            if let Fragment::Initial(params) = fragment {
+                for param in params.iter() {
+                    match param {
+                        ConfigParam::Block0Date(date) => block0_time = Some(date.0),
+                        ConfigParam::SlotDuration(duration) => {
+                            slot_duration = Some(*duration as u64)
+                        }
+                        ConfigParam::SlotsPerEpoch(slots) => slots_per_epoch = Some(*slots),
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        Self::new(
+            block0_time.ok_or(Error::Missing("block0 date"))?,
+            slot_duration.ok_or(Error::Missing("slot duration"))?,
+            slots_per_epoch.ok_or(Error::Missing("slots per epoch"))?,
+        )
+    }
+
+    /// the slots must last and the epochs have slots for the dates to be
+    /// computed
+    fn new(block0_time: u64, slot_duration: u64, slots_per_epoch: u32) -> Result<Self, Error> {
+        if slot_duration == 0 {
+            return Err(Error::Zero("slot duration"));
+        }
+        if slots_per_epoch == 0 {
+            return Err(Error::Zero("slots per epoch"));
+        }
+
+        Ok(Self {
+            block0_time,
+            slot_duration,
+            slots_per_epoch,
+            offset: 0,
+        })
+    }
+
+    /// follow the node's clock, given the start of its current slot and the
+    /// local time it was received at.
+    ///
+    /// The local clock is only corrected when it is not in the node's current
+    /// slot, the node does not tell where it is in the slot.
+    pub fn sync(&mut self, slot_start: SystemTime, local: SystemTime) {
+        let slot_end = slot_start + Duration::from_secs(self.slot_duration);
+
+        self.offset = if local < slot_start {
+            millis(slot_start.duration_since(local).unwrap_or_default())
+        } else if local >= slot_end {
+            // back to the last millisecond of the slot
+            -millis(local.duration_since(slot_end).unwrap_or_default()) - 1
+        } else {
+            0
+        };
+    }
+
+    /// the node's time at the given local time
+    pub fn node_time(&self, local: SystemTime) -> SystemTime {
+        if self.offset >= 0 {
+            local + Duration::from_millis(self.offset as u64)
+        } else {
+            local - Duration::from_millis(self.offset.wrapping_neg() as u64)
+        }
+    }
+
+    /// the node's time now
+    pub fn now(&self) -> SystemTime {
+        self.node_time(SystemTime::now())
+    }
+
+    /// the block date of the given time, `None` before the block0
+    pub fn date(&self, time: SystemTime) -> Option<BlockDate> {
+        let since_block0 = time
+            .duration_since(UNIX_EPOCH + Duration::from_secs(self.block0_time))
+            .ok()?;
+        let slots = since_block0.as_secs() / self.slot_duration;
+        let slots_per_epoch = self.slots_per_epoch as u64;
+
+        Some(BlockDate {
+            epoch: (slots / slots_per_epoch) as u32,
+            slot_id: (slots % slots_per_epoch) as u32,
+        })
+    }
+
+    /// the time the slot of the given date starts
+    pub fn time(&self, date: BlockDate) -> SystemTime {
+        let slots = date.epoch as u64 * self.slots_per_epoch as u64 + date.slot_id as u64;
+        UNIX_EPOCH + Duration::from_secs(self.block0_time + slots * self.slot_duration)
+    }
+
+    /// whether the votes are accepted at the given time
+    pub fn status(&self, window: VoteWindow, time: SystemTime) -> WindowStatus {
+        let start = self.time(window.start);
+        let end = self.time(window.end);
+
+        if let Ok(delay) = start.duration_since(time) {
+            if delay > Duration::default() {
+                return WindowStatus::Upcoming(delay);
+            }
+        }
+        match end.duration_since(time) {
+            Ok(remaining) if remaining > Duration::default() => WindowStatus::Open(remaining),
+            _ => WindowStatus::Closed,
+        }
+    }
+}
+
+fn millis(duration: Duration) -> i64 {
+    duration.as_millis().min(i64::MAX as u128) as i64
+}
+
+impl WindowStatus {
+    pub fn is_open(self) -> bool {
+        matches!(self, WindowStatus::Open(_))
+    }
+}
+
+/// a message every given period, to refresh the countdowns
+pub fn every(period: Duration) -> iced::Subscription<()> {
+    iced::Subscription::from_recipe(Every { period })
+}
+
+pub struct Every {
+    period: Duration,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for Every
+where
+    H: std::hash::Hasher,
+{
+    type Output = ();
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.period.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        let period = self.period;
+
+        Box::pin(futures::stream::unfold((), move |()| async move {
+            tokio::time::delay_for(period).await;
+            Some(((), ()))
+        }))
+    }
+}
+
+/// a duration as days, hours, minutes and seconds
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (days, hours, minutes, seconds) = (
+        seconds / 86400,
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+    );
+
+    if days > 0 {
+        format!("{}d {:02}h {:02}m {:02}s", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Invalid(error) => write!(f, "invalid block0: {}", error),
+            Error::Missing(parameter) => write!(f, "the block0 does not set the {}", parameter),
+            Error::Zero(parameter) => write!(f, "the block0 sets the {} to zero", parameter),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Invalid(error) => Some(error),
+            Error::Missing(_) | Error::Zero(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK0_TIME: u64 = 1_600_000_000;
+
+    fn clock() -> Clock {
+        // 10 seconds slots, 100 slots per epoch
+        Clock::new(BLOCK0_TIME, 10, 100).unwrap()
+    }
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(BLOCK0_TIME + seconds)
+    }
+
+    fn date(epoch: u32, slot_id: u32) -> BlockDate {
+        BlockDate { epoch, slot_id }
+    }
+
+    #[test]
+    fn reject_zero_parameters() {
+        assert!(matches!(
+            Clock::new(BLOCK0_TIME, 0, 100),
+            Err(Error::Zero("slot duration"))
+        ));
+        assert!(matches!(
+            Clock::new(BLOCK0_TIME, 10, 0),
+            Err(Error::Zero("slots per epoch"))
+        ));
+    }
+
+    #[test]
+    fn convert_dates() {
+        let clock = clock();
+
+        assert_eq!(clock.date(at(0)), Some(date(0, 0)));
+        assert_eq!(clock.date(at(25)), Some(date(0, 2)));
+        assert_eq!(clock.date(at(1_030)), Some(date(1, 3)));
+        assert_eq!(clock.date(UNIX_EPOCH), None);
+        assert_eq!(clock.time(date(1, 3)), at(1_030));
+    }
+
+    #[test]
+    fn window_status() {
+        let clock = clock();
+        let window = VoteWindow {
+            start: date(0, 10),
+            end: date(0, 20),
+        };
+
+        assert_eq!(
+            clock.status(window, at(95)),
+            WindowStatus::Upcoming(Duration::from_secs(5))
+        );
+        assert_eq!(
+            clock.status(window, at(100)),
+            WindowStatus::Open(Duration::from_secs(100))
+        );
+        assert_eq!(
+            clock.status(window, at(199)),
+            WindowStatus::Open(Duration::from_secs(1))
+        );
+        assert_eq!(clock.status(window, at(200)), WindowStatus::Closed);
+    }
+
+    #[test]
+    fn sync_with_the_node() {
+        let mut clock = clock();
+        let slot_start = at(100);
+
+        // the local time is in the node's slot
+        clock.sync(slot_start, at(105));
+        assert_eq!(clock.node_time(at(105)), at(105));
+
+        // the local clock is late
+        clock.sync(slot_start, at(70));
+        assert_eq!(clock.node_time(at(70)), slot_start);
+        assert_eq!(clock.node_time(at(80)), at(110));
+
+        // the local clock is early
+        clock.sync(slot_start, at(130));
+        assert_eq!(clock.node_time(at(130)), at(110) - Duration::from_millis(1));
+        assert_eq!(clock.date(clock.node_time(at(130))), Some(date(0, 10)));
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    fs,
+    io::{self, BufRead as _, Write as _},
+    path::Path,
+};
+use tokio::runtime::Runtime;
+use wallet_core as chain;
+use zeroize::Zeroizing;
+
+use crate::{
+    arguments::{Arguments, Cli, FragmentFormat, Secret},
+    block0,
+    config::{Config, Endpoints, PayloadType},
+    format_value, keystore, mnemonic, node_client, node_settings, secret_key, send_transaction,
+    vote_plans::VotePlanStatus,
+    wallet_state::AccountState,
+    Choice, Wallet,
+};
+
+type Error = Box<dyn std::error::Error>;
+
+/// run one of the command line commands, the result is printed on the
+/// standard output
+pub fn run(arguments: &Arguments, cli: &Cli, runtime: &mut Runtime) -> Result<(), Error> {
+    let config = Config::load(&arguments.config)?;
+    let endpoints = config.endpoints(arguments)?;
+
+    match cli {
+        Cli::Proposals => {
+            for (index, proposal) in config.proposals()?.iter().enumerate() {
+                println!("{}: {}", index, proposal.title);
+                if proposal.payload_type == PayloadType::Private {
+                    println!("    private vote");
+                }
+                if proposal.voting_power_threshold > 0 {
+                    println!(
+                        "    minimum voting power: {}",
+                        format_value(proposal.voting_power_threshold)
+                    );
+                }
+                for (choice, label) in proposal.choices() {
+                    println!("    {}: {}", choice.0, label);
+                }
+            }
+        }
+        Cli::VotePlans => {
+            let plans: Vec<VotePlanStatus> =
+                runtime.block_on(node_client::get(endpoints.vote_plans()))?;
+            for plan in plans.iter() {
+                println!("{} ({:?})", plan.id, plan.payload);
+                println!(
+                    "    vote: {} to {}, tally until {}",
+                    plan.vote_start, plan.vote_end, plan.committee_end
+                );
+                for proposal in plan.proposals.iter() {
+                    println!(
+                        "    {}: {} ({} options, {} votes cast)",
+                        proposal.index,
+                        proposal.proposal_id,
+                        proposal.options.end.saturating_sub(proposal.options.start),
+                        proposal.votes_cast
+                    );
+                }
+            }
+        }
+        Cli::Recover(secret) => {
+            let wallet = recover(arguments, &config, &endpoints, secret, runtime)?;
+            for (index, account) in wallet.accounts.iter().enumerate() {
+                println!("{}: {}", index, account.id);
+            }
+        }
+        Cli::AccountState(secret) => {
+            let wallet = recover(arguments, &config, &endpoints, secret, runtime)?;
+            for (index, account) in wallet.accounts.iter().enumerate() {
+                let url = endpoints.account(&account.id);
+                println!("{}: {}", index, account.id);
+                match runtime.block_on(node_client::get::<AccountState, _>(url)) {
+                    Ok(account_state) => {
+                        println!("    value: {}", account_state.value);
+                        println!("    counter: {}", account_state.counter);
+                        println!("    delegation: {}", account_state.delegation);
+                        if let Some(rewards) = &account_state.last_rewards {
+                            println!(
+                                "    last rewards: {} (epoch {})",
+                                rewards.reward, rewards.epoch
+                            );
+                        }
+                        for (name, value) in account_state.other.iter() {
+                            println!("    {}: {}", name, value);
+                        }
+                    }
+                    Err(error) => println!("    {}", error),
+                }
+            }
+        }
+        Cli::Vote {
+            secret,
+            proposal,
+            choice,
+            account,
+            value,
+            counter,
+            submit,
+            output,
+            format,
+        } => {
+            // only the votes need the configured proposals
+            let proposals = config.proposals()?;
+            let mut wallet = recover(arguments, &config, &endpoints, secret, runtime)?;
+            wallet.proposals = proposals;
+            let choice = find_choice(&wallet, *proposal, *choice)?;
+            select_account(&mut wallet, *account)?;
+
+            // offline the account state is given instead of queried
+            let (value, counter) = match (value, counter) {
+                (Some(value), Some(counter)) => (*value, *counter),
+                _ => {
+                    let url = endpoints.account(&wallet.accounts[*account].id);
+                    let account_state: AccountState = runtime.block_on(node_client::get(url))?;
+                    (account_state.value, account_state.counter)
+                }
+            };
+            wallet.set_state(*account, chain::Value(value), counter)?;
+
+            let threshold = wallet.proposals[*proposal].voting_power_threshold;
+            if value < threshold {
+                return Err(format!(
+                    "the account has a voting power of {}, {} are needed to vote on the proposal",
+                    format_value(value),
+                    format_value(threshold)
+                )
+                .into());
+            }
+
+            if let Some(fee) = wallet.vote_fee().filter(|fee| value < *fee) {
+                return Err(format!(
+                    "the account has {}, it cannot pay the fee of {} of the vote",
+                    format_value(value),
+                    format_value(fee)
+                )
+                .into());
+            }
+
+            let mut choices = vec![None; wallet.proposals.len()];
+            choices[*proposal] = Some(choice);
+            wallet.make_choices(&choices);
+
+            let vote = match (wallet.votes.pop(), wallet.skipped.pop()) {
+                (Some(vote), _) => vote,
+                (None, Some(skipped)) => return Err(skipped.reason.into()),
+                (None, None) => return Err("cannot build the vote fragment".into()),
+            };
+            if *submit {
+                let id =
+                    runtime.block_on(send_transaction::send(&endpoints.fragment, vote.fragment))?;
+                println!("{}", id);
+            } else {
+                let bytes = match format {
+                    FragmentFormat::Hex => {
+                        format!("{}\n", hex::encode(&vote.fragment)).into_bytes()
+                    }
+                    FragmentFormat::Binary => vote.fragment.to_vec(),
+                };
+                match output {
+                    Some(path) => fs::write(path, bytes)?,
+                    None => io::stdout().write_all(&bytes)?,
+                }
+            }
+        }
+        Cli::Submit { fragment, input } => {
+            let fragment = match (fragment, input) {
+                (Some(fragment), _) => hex::decode(fragment.trim())?,
+                (None, Some(path)) => read_fragment(path)?,
+                (None, None) => hex::decode(read_line()?.trim())?,
+            }
+            .into_boxed_slice();
+
+            let id = runtime.block_on(send_transaction::send(&endpoints.fragment, fragment))?;
+            println!("{}", id);
+        }
+    }
+
+    Ok(())
+}
+
+fn recover(
+    arguments: &Arguments,
+    config: &Config,
+    endpoints: &Endpoints,
+    secret: &Secret,
+    runtime: &mut Runtime,
+) -> Result<Wallet, Error> {
+    let block0 = runtime.block_on(block0::load(&config.block0(arguments)?, endpoints))?;
+    // the votes can still be signed with the block0's settings
+    let live_settings = if config.live_settings(arguments)? {
+        match runtime.block_on(node_settings::get(endpoints.settings(), &block0)) {
+            Ok(settings) => Some(settings),
+            // the votes would be sent to another blockchain
+            Err(error @ node_settings::Error::Block0(_)) => return Err(error.into()),
+            Err(error) => {
+                eprintln!("{}, using the settings of the block0", error);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let mut wallet = Wallet::new(Vec::new(), config.vote_plans.clone(), block0);
+    wallet.live_settings = live_settings;
+
+    let mnemonics = Zeroizing::new(match &secret.mnemonics {
+        Some(mnemonics) => mnemonics.clone(),
+        None => read_line()?,
+    });
+    let mnemonics = mnemonics.trim();
+    if !secret_key::is_secret_key(mnemonics) {
+        let status = mnemonic::check(mnemonics);
+        if !status.is_complete() {
+            return Err(format!("invalid mnemonics: {}", status).into());
+        }
+    }
+    let passphrase = secret.passphrase.as_deref().unwrap_or_default();
+    wallet.recover(&keystore::Content::parse(mnemonics, passphrase)?)?;
+    for change in wallet.settings_changes.iter() {
+        eprintln!("using the settings of the node, {}", change);
+    }
+
+    Ok(wallet)
+}
+
+/// make the account the only one voting
+fn select_account(wallet: &mut Wallet, account: usize) -> Result<(), Error> {
+    if account >= wallet.accounts.len() {
+        return Err(format!(
+            "no account {}, the wallet has {} accounts",
+            account,
+            wallet.accounts.len()
+        )
+        .into());
+    }
+
+    for (index, other) in wallet.accounts.iter_mut().enumerate() {
+        other.selected = index == account;
+    }
+    Ok(())
+}
+
+fn find_choice(wallet: &Wallet, proposal: usize, choice: u8) -> Result<Choice, Error> {
+    let proposal = wallet.proposals.get(proposal).ok_or_else(|| {
+        format!(
+            "no proposal {}, there are {} proposals",
+            proposal,
+            wallet.proposals.len()
+        )
+    })?;
+
+    proposal
+        .choices()
+        .map(|(choice, _)| choice)
+        .find(|c| c.0 == choice)
+        .ok_or_else(|| format!("invalid choice {} for '{}'", choice, proposal.title).into())
+}
+
+fn read_line() -> Result<String, Error> {
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line)
+}
+
+/// read an exported fragment, either hex encoded or binary
+fn read_fragment(path: &Path) -> Result<Vec<u8>, Error> {
+    let bytes = fs::read(path)?;
+
+    match std::str::from_utf8(&bytes).map(|hex| hex::decode(hex.trim())) {
+        Ok(Ok(fragment)) => Ok(fragment),
+        _ => Ok(bytes),
+    }
+}
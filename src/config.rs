@@ -0,0 +1,692 @@
+use chain_impl_mockchain::certificate::VotePlanId;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use wallet_core as chain;
+
+use crate::{arguments::Arguments, block0, vote_plans::VotePlanStatus, Proposal};
+
+/// network profile used when none is selected
+pub const DEFAULT_NETWORK: &str = "local";
+
+/// REST API of a node running locally with the default settings
+const LOCAL_REST: &str = "http://127.0.0.1:8443/api";
+/// how long to wait, in seconds, for a vote to be in a block when the profile
+/// does not tell
+const DEFAULT_CONFIRMATION_TIMEOUT: u64 = 300;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    /// name of the network profile to use
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub networks: BTreeMap<String, Network>,
+    #[serde(default)]
+    pub vote_plans: Vec<VotePlan>,
+    /// directory of the configuration file, the relative paths of the
+    /// profiles are relative to it
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+/// a named network profile
+#[derive(Debug, Clone, Deserialize)]
+pub struct Network {
+    /// base URL of the node REST API (e.g. `http://127.0.0.1:8443/api`)
+    pub rest: String,
+    /// URL the fragments are posted to, `{rest}/v0/message` by default
+    #[serde(default)]
+    pub fragment: Option<String>,
+    /// link to a fragment in a block explorer, `{}` is replaced by the fragment id
+    #[serde(default)]
+    pub explorer: Option<String>,
+    /// hash of the network's block0
+    #[serde(default)]
+    pub genesis_hash: Option<String>,
+    /// block0 file of the network, downloaded from the node if not given
+    #[serde(default)]
+    pub block0: Option<PathBuf>,
+    /// how long to wait, in seconds, for a sent vote to be in a block
+    #[serde(default)]
+    pub confirmation_timeout: Option<u64>,
+    /// sign the votes with the fees and discrimination the node currently
+    /// applies rather than the block0's ones
+    #[serde(default)]
+    pub live_settings: bool,
+}
+
+/// the endpoints of the selected network, after applying the command line
+/// and environment overrides
+#[derive(Debug, Clone, Default)]
+pub struct Endpoints {
+    pub rest: String,
+    pub fragment: String,
+    pub explorer: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VotePlan {
+    pub id: String,
+    #[serde(default)]
+    pub payload_type: PayloadType,
+    /// minimum value, in lovelace, an account needs to vote on the plan's
+    /// proposals
+    #[serde(default)]
+    pub voting_power_threshold: u64,
+    #[serde(default)]
+    pub proposals: Vec<ProposalConfig>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadType {
+    Public,
+    /// the choices are encrypted to the committee, the wallet library cannot
+    /// sign these votes yet
+    Private,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProposalConfig {
+    pub index: u8,
+    pub title: String,
+    pub options: u8,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    Toml {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+    Json {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+    NoProposals,
+    UnknownNetwork(String),
+    InvalidVotePlanId {
+        id: String,
+        error: String,
+    },
+    InvalidOptions {
+        vote_plan: String,
+        index: u8,
+        options: u8,
+    },
+    InvalidLabels {
+        vote_plan: String,
+        index: u8,
+        options: u8,
+        labels: usize,
+    },
+    /// the wallet library cannot encrypt the choices of private votes
+    PrivateVotesUnsupported {
+        vote_plan: String,
+    },
+}
+
+impl Default for PayloadType {
+    fn default() -> Self {
+        PayloadType::Public
+    }
+}
+
+impl Config {
+    /// load the configuration file. The file is parsed as JSON if it has the
+    /// `.json` extension, as TOML otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|error| Error::Io {
+            path: path.to_owned(),
+            error,
+        })?;
+
+        let is_json = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+
+        let config: Self = if is_json {
+            serde_json::from_str(&content).map_err(|error| Error::Json {
+                path: path.to_owned(),
+                error,
+            })?
+        } else {
+            toml::from_str(&content).map_err(|error| Error::Toml {
+                path: path.to_owned(),
+                error,
+            })?
+        };
+
+        Ok(Self {
+            dir: path.parent().map(Path::to_owned).unwrap_or_default(),
+            ..config
+        })
+    }
+
+    /// build every proposal the app can vote on
+    pub fn proposals(&self) -> Result<Vec<Proposal>, Error> {
+        let mut proposals = Vec::new();
+
+        for vote_plan in self.vote_plans.iter() {
+            proposals.extend(vote_plan.proposals()?);
+        }
+
+        if proposals.is_empty() {
+            Err(Error::NoProposals)
+        } else {
+            Ok(proposals)
+        }
+    }
+
+    /// endpoints of the selected network profile. The URLs given on the command
+    /// line or in the environment take precedence over the profile's.
+    ///
+    /// The `local` profile is always available, it can be overridden in the
+    /// configuration file too.
+    pub fn endpoints(&self, arguments: &Arguments) -> Result<Endpoints, Error> {
+        let network = self.network(arguments)?;
+
+        let rest = arguments
+            .rest_url
+            .clone()
+            .unwrap_or(network.rest)
+            .trim_end_matches('/')
+            .to_owned();
+        let fragment = arguments
+            .fragment_url
+            .clone()
+            .or(network.fragment)
+            .unwrap_or_else(|| format!("{}/v0/message", rest));
+        let explorer = arguments.explorer_url.clone().or(network.explorer);
+
+        Ok(Endpoints {
+            rest,
+            fragment,
+            explorer,
+        })
+    }
+
+    /// where to find the block0 of the selected network profile, the command
+    /// line and the environment taking precedence over the profile.
+    pub fn block0(&self, arguments: &Arguments) -> Result<block0::Source, Error> {
+        let network = self.network(arguments)?;
+
+        // the command line path is relative to the current directory and the
+        // profile's one to the configuration file
+        let profile_block0 = network.block0.map(|path| self.dir.join(path));
+
+        Ok(block0::Source {
+            path: arguments.block0.clone().or(profile_block0),
+            genesis_hash: arguments.genesis_hash.clone().or(network.genesis_hash),
+        })
+    }
+
+    /// how long to wait for a sent vote to be in a block, the command line
+    /// and the environment taking precedence over the profile.
+    pub fn confirmation_timeout(&self, arguments: &Arguments) -> Result<Duration, Error> {
+        let network = self.network(arguments)?;
+
+        let seconds = arguments
+            .confirmation_timeout
+            .or(network.confirmation_timeout)
+            .unwrap_or(DEFAULT_CONFIRMATION_TIMEOUT);
+
+        Ok(Duration::from_secs(seconds))
+    }
+
+    /// whether the settings of the node are used instead of the block0's, if
+    /// asked on the command line or in the profile
+    pub fn live_settings(&self, arguments: &Arguments) -> Result<bool, Error> {
+        Ok(arguments.live_settings || self.network(arguments)?.live_settings)
+    }
+
+    fn network(&self, arguments: &Arguments) -> Result<Network, Error> {
+        let name = arguments
+            .network
+            .as_ref()
+            .or(self.network.as_ref())
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_NETWORK);
+
+        match self.networks.get(name) {
+            Some(network) => Ok(network.clone()),
+            None if name == DEFAULT_NETWORK => Ok(Network::new(LOCAL_REST.to_owned())),
+            None => Err(Error::UnknownNetwork(name.to_owned())),
+        }
+    }
+}
+
+impl Network {
+    fn new(rest: String) -> Self {
+        Self {
+            rest,
+            fragment: None,
+            explorer: None,
+            genesis_hash: None,
+            block0: None,
+            confirmation_timeout: None,
+            live_settings: false,
+        }
+    }
+}
+
+impl Endpoints {
+    /// URL of the state of the given account
+    pub fn account(&self, id: &str) -> String {
+        format!("{}/v0/account/{}", self.rest, id)
+    }
+
+    /// URL of the block of the given hash
+    pub fn block(&self, hash: &str) -> String {
+        format!("{}/v0/block/{}", self.rest, hash)
+    }
+
+    /// URL of the settings the node currently applies
+    pub fn settings(&self) -> String {
+        format!("{}/v0/settings", self.rest)
+    }
+
+    /// URL of the vote plans active on the node
+    pub fn vote_plans(&self) -> String {
+        format!("{}/v0/vote/active/plans", self.rest)
+    }
+
+    /// URL of the logs of the fragments the node received
+    pub fn fragment_logs(&self) -> String {
+        format!("{}/v0/fragment/logs", self.rest)
+    }
+
+    /// link to the given fragment in the block explorer, if one is configured
+    pub fn explorer_link(&self, fragment_id: &str) -> Option<String> {
+        self.explorer
+            .as_ref()
+            .map(|template| template.replace("{}", fragment_id))
+    }
+}
+
+impl VotePlan {
+    /// the vote plan as it is active on the node, completed with the
+    /// configured one of the same id: the titles, labels and minimum voting
+    /// power only come from the configuration
+    pub fn active(status: &VotePlanStatus, configured: Option<&VotePlan>) -> Self {
+        let proposals = status
+            .proposals
+            .iter()
+            .map(|proposal| {
+                let options = proposal.options.end.saturating_sub(proposal.options.start);
+                let configured = configured
+                    .and_then(|plan| plan.proposals.iter().find(|p| p.index == proposal.index));
+
+                match configured {
+                    Some(configured) => ProposalConfig {
+                        options,
+                        ..configured.clone()
+                    },
+                    None => ProposalConfig {
+                        index: proposal.index,
+                        title: format!("Proposal {} ({})", proposal.index, proposal.proposal_id),
+                        options,
+                        labels: Vec::new(),
+                    },
+                }
+            })
+            .collect();
+
+        Self {
+            id: status.id.clone(),
+            payload_type: status.payload,
+            voting_power_threshold: configured
+                .map(|plan| plan.voting_power_threshold)
+                .unwrap_or(0),
+            proposals,
+        }
+    }
+
+    /// build every proposal of the vote plan
+    pub fn proposals(&self) -> Result<Vec<Proposal>, Error> {
+        self.proposals
+            .iter()
+            .map(|proposal| self.proposal(proposal))
+            .collect()
+    }
+
+    fn proposal(&self, proposal: &ProposalConfig) -> Result<Proposal, Error> {
+        if !proposal.labels.is_empty() && proposal.labels.len() != proposal.options as usize {
+            return Err(Error::InvalidLabels {
+                vote_plan: self.id.clone(),
+                index: proposal.index,
+                options: proposal.options,
+                labels: proposal.labels.len(),
+            });
+        }
+
+        let id = self
+            .id
+            .parse::<VotePlanId>()
+            .map_err(|error| Error::InvalidVotePlanId {
+                id: self.id.clone(),
+                error: error.to_string(),
+            })?;
+        let options =
+            chain::Options::new_length(proposal.options).map_err(|_| Error::InvalidOptions {
+                vote_plan: self.id.clone(),
+                index: proposal.index,
+                options: proposal.options,
+            })?;
+
+        Ok(Proposal {
+            title: proposal.title.clone(),
+            labels: proposal.labels.clone(),
+            options: options.clone(),
+            voting_power_threshold: self.voting_power_threshold,
+            payload_type: self.payload_type,
+            vote_plan: self.id.clone(),
+            window: None,
+            selected: true,
+            proposal: chain::Proposal::new(id, self.payload_type()?, proposal.index, options),
+        })
+    }
+
+    /// the payload type of the votes, the wallet library only signs public
+    /// votes
+    fn payload_type(&self) -> Result<chain::PayloadType, Error> {
+        match self.payload_type {
+            PayloadType::Public => Ok(chain::PayloadType::Public),
+            PayloadType::Private => Err(Error::PrivateVotesUnsupported {
+                vote_plan: self.id.clone(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, error } => {
+                write!(f, "cannot read configuration {}: {}", path.display(), error)
+            }
+            Error::Toml { path, error } => {
+                write!(f, "invalid configuration {}: {}", path.display(), error)
+            }
+            Error::Json { path, error } => {
+                write!(f, "invalid configuration {}: {}", path.display(), error)
+            }
+            Error::NoProposals => write!(f, "no proposal configured in any vote plan"),
+            Error::UnknownNetwork(name) => write!(f, "unknown network profile '{}'", name),
+            Error::InvalidVotePlanId { id, error } => {
+                write!(f, "invalid vote plan id '{}': {}", id, error)
+            }
+            Error::InvalidOptions {
+                vote_plan,
+                index,
+                options,
+            } => write!(
+                f,
+                "proposal {} of vote plan {}: invalid number of options ({})",
+                index, vote_plan, options
+            ),
+            Error::InvalidLabels {
+                vote_plan,
+                index,
+                options,
+                labels,
+            } => write!(
+                f,
+                "proposal {} of vote plan {}: {} labels given for {} options",
+                index, vote_plan, labels, options
+            ),
+            Error::PrivateVotesUnsupported { vote_plan } => write!(
+                f,
+                "vote plan {}: the wallet library cannot sign private votes yet",
+                vote_plan
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            Error::Toml { error, .. } => Some(error),
+            Error::Json { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vote_plans::{BlockDate, ProposalStatus};
+    use structopt::StructOpt as _;
+
+    const VOTE_PLAN: &str = "d5bd73ca1b2cb59c44e9ca2e4aa3e4bc1a1aba2862fce19a9516e5041abfe92f";
+
+    fn arguments(args: &[&str]) -> Arguments {
+        Arguments::from_iter(std::iter::once("jorvote").chain(args.iter().cloned()))
+    }
+
+    fn vote_plan(payload_type: PayloadType) -> VotePlan {
+        VotePlan {
+            id: VOTE_PLAN.to_owned(),
+            payload_type,
+            voting_power_threshold: 1_000_000,
+            proposals: vec![ProposalConfig {
+                index: 0,
+                title: "Continue the testnet".to_owned(),
+                options: 2,
+                labels: vec!["Yes".to_owned(), "No".to_owned()],
+            }],
+        }
+    }
+
+    #[test]
+    fn load_shipped_configuration() {
+        let config: Config = toml::from_str(include_str!("../jorvote.toml")).unwrap();
+
+        assert!(config.networks.contains_key("local"));
+        // the Incentivized TestNet is gone
+        assert_eq!(config.networks.len(), 1);
+        assert!(!config.proposals().unwrap().is_empty());
+    }
+
+    #[test]
+    fn load_json_configuration() {
+        let config: Config = serde_json::from_str(&format!(
+            r#"{{
+                "network": "devnet",
+                "networks": {{ "devnet": {{ "rest": "http://127.0.0.1:3000/api" }} }},
+                "vote_plans": [{{
+                    "id": "{}",
+                    "proposals": [{{ "index": 1, "title": "Fees", "options": 3 }}]
+                }}]
+            }}"#,
+            VOTE_PLAN
+        ))
+        .unwrap();
+
+        let endpoints = config.endpoints(&arguments(&[])).unwrap();
+        assert_eq!(endpoints.rest, "http://127.0.0.1:3000/api");
+        assert_eq!(config.vote_plans[0].payload_type, PayloadType::Public);
+        assert_eq!(config.vote_plans[0].voting_power_threshold, 0);
+        assert_eq!(config.proposals().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn resolve_block0_from_the_configuration() {
+        let dir = std::env::temp_dir().join(format!("jorvote-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("jorvote.toml");
+        fs::write(
+            &path,
+            r#"
+            [networks.local]
+            rest = "http://127.0.0.1:8443/api"
+            block0 = "block0.bin"
+            "#,
+        )
+        .unwrap();
+        let config = Config::load(&path);
+        fs::remove_dir_all(&dir).unwrap();
+        let config = config.unwrap();
+
+        let source = config.block0(&arguments(&[])).unwrap();
+        assert_eq!(source.path, Some(dir.join("block0.bin")));
+        // the command line path stays relative to the current directory
+        let source = config
+            .block0(&arguments(&["--block0", "other.bin"]))
+            .unwrap();
+        assert_eq!(source.path, Some(PathBuf::from("other.bin")));
+    }
+
+    #[test]
+    fn select_networks() {
+        let config: Config = toml::from_str(
+            r#"
+            [networks.testnet]
+            rest = "https://testnet.example/api/"
+            explorer = "https://explorer.example/tx/{}"
+            live_settings = true
+            "#,
+        )
+        .unwrap();
+
+        let endpoints = config.endpoints(&arguments(&[])).unwrap();
+        assert_eq!(endpoints.rest, LOCAL_REST);
+        assert_eq!(endpoints.fragment, format!("{}/v0/message", LOCAL_REST));
+        assert!(!config.live_settings(&arguments(&[])).unwrap());
+
+        let testnet = arguments(&["--network", "testnet"]);
+        let endpoints = config.endpoints(&testnet).unwrap();
+        assert_eq!(endpoints.rest, "https://testnet.example/api");
+        assert_eq!(
+            endpoints.explorer_link("abc").as_deref(),
+            Some("https://explorer.example/tx/abc")
+        );
+        assert!(config.live_settings(&testnet).unwrap());
+
+        let overridden = arguments(&[
+            "--network",
+            "testnet",
+            "--rest-url",
+            "http://node:8443/api",
+            "--fragment-url",
+            "http://relay:8443/api/v0/message",
+        ]);
+        let endpoints = config.endpoints(&overridden).unwrap();
+        assert_eq!(endpoints.rest, "http://node:8443/api");
+        assert_eq!(endpoints.fragment, "http://relay:8443/api/v0/message");
+    }
+
+    #[test]
+    fn reject_unknown_networks() {
+        let config: Config = toml::from_str("").unwrap();
+
+        for args in [
+            &["--network", "mainnet"][..],
+            &["--network", "mainnet", "--rest-url", "http://node:8443/api"][..],
+        ]
+        .iter()
+        {
+            match config.endpoints(&arguments(args)) {
+                Err(Error::UnknownNetwork(name)) => assert_eq!(name, "mainnet"),
+                result => panic!("unexpected result {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn build_proposals() {
+        let proposals = vote_plan(PayloadType::Public).proposals().unwrap();
+
+        assert_eq!(proposals.len(), 1);
+        let proposal = &proposals[0];
+        assert_eq!(proposal.title, "Continue the testnet");
+        assert_eq!(proposal.vote_plan, VOTE_PLAN);
+        assert_eq!(proposal.voting_power_threshold, 1_000_000);
+        assert_eq!(proposal.options.choice_range(), &(0..2));
+    }
+
+    #[test]
+    fn reject_invalid_proposals() {
+        let mut plan = vote_plan(PayloadType::Public);
+        plan.proposals[0].labels.pop();
+        assert!(matches!(
+            plan.proposals(),
+            Err(Error::InvalidLabels { labels: 1, .. })
+        ));
+
+        let mut plan = vote_plan(PayloadType::Public);
+        plan.id = "not a vote plan".to_owned();
+        assert!(matches!(
+            plan.proposals(),
+            Err(Error::InvalidVotePlanId { .. })
+        ));
+
+        let config: Config = toml::from_str("").unwrap();
+        assert!(matches!(config.proposals(), Err(Error::NoProposals)));
+    }
+
+    #[test]
+    fn reject_private_votes() {
+        let plan = vote_plan(PayloadType::Private);
+        assert!(matches!(
+            plan.proposals(),
+            Err(Error::PrivateVotesUnsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn complete_active_plans() {
+        let date = |epoch| BlockDate { epoch, slot_id: 0 };
+        let status = VotePlanStatus {
+            id: VOTE_PLAN.to_owned(),
+            payload: PayloadType::Private,
+            vote_start: date(1),
+            vote_end: date(2),
+            committee_end: date(3),
+            proposals: vec![
+                ProposalStatus {
+                    index: 0,
+                    proposal_id: "aa".to_owned(),
+                    options: 0..3,
+                    votes_cast: 0,
+                },
+                ProposalStatus {
+                    index: 1,
+                    proposal_id: "bb".to_owned(),
+                    options: 0..2,
+                    votes_cast: 0,
+                },
+            ],
+        };
+        let configured = vote_plan(PayloadType::Public);
+
+        let plan = VotePlan::active(&status, Some(&configured));
+        // the node tells the payload type and the options
+        assert_eq!(plan.payload_type, PayloadType::Private);
+        assert_eq!(plan.voting_power_threshold, 1_000_000);
+        assert_eq!(plan.proposals[0].title, "Continue the testnet");
+        assert_eq!(plan.proposals[0].options, 3);
+        assert_eq!(plan.proposals[1].title, "Proposal 1 (bb)");
+        assert!(plan.proposals[1].labels.is_empty());
+
+        let plan = VotePlan::active(&status, None);
+        assert_eq!(plan.voting_power_threshold, 0);
+        assert_eq!(plan.proposals[0].title, "Proposal 0 (aa)");
+    }
+}
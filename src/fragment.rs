@@ -0,0 +1,130 @@
+use chain_core::property::Deserialize as _;
+use chain_impl_mockchain::{fragment::Fragment, transaction::InputEnum, vote::Payload};
+
+/// the content of a signed vote fragment, as it will be read by the node
+#[derive(Debug, Clone)]
+pub struct VoteSummary {
+    pub fragment_id: String,
+    pub vote_plan: String,
+    pub proposal_index: u8,
+    /// the chosen option, `None` if the vote is private
+    pub choice: Option<u8>,
+    pub fee: u64,
+    /// the account paying for the vote, hex encoded
+    pub account: Option<String>,
+}
+
+/// decode a vote fragment as built by the wallet
+pub fn decode(bytes: &[u8]) -> Result<VoteSummary, String> {
+    let fragment = Fragment::deserialize(bytes).map_err(|error| error.to_string())?;
+    let fragment_id = fragment.hash().to_string();
+
+    let tx = match fragment {
+        Fragment::VoteCast(tx) => tx,
+        _ => return Err("the fragment is not a vote".to_owned()),
+    };
+    let tx = tx.as_slice();
+
+    let vote_cast = tx.payload().into_payload();
+    let choice = match vote_cast.payload() {
+        Payload::Public { choice } => Some(choice.as_byte()),
+        _ => None,
+    };
+
+    let total_input = tx.total_input().map_err(|error| error.to_string())?;
+    let total_output = tx.total_output().map_err(|error| error.to_string())?;
+    let fee = (total_input - total_output).map_err(|error| error.to_string())?;
+
+    let account = tx.inputs().iter().find_map(|input| match input.to_enum() {
+        InputEnum::AccountInput(account, _) => Some(hex::encode(account.as_ref())),
+        InputEnum::UtxoInput(_) => None,
+    });
+
+    Ok(VoteSummary {
+        fragment_id,
+        vote_plan: vote_cast.vote_plan().to_string(),
+        proposal_index: vote_cast.proposal_index(),
+        choice,
+        fee: fee.0,
+        account,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_core::property::{Deserialize as _, Serialize as _};
+    use chain_crypto::{Ed25519, SecretKey, Signature};
+    use chain_impl_mockchain::{
+        account,
+        certificate::{VoteCast, VotePlanId},
+        config::ConfigParams,
+        transaction::{Input, TxBuilder, Witness},
+        value::Value,
+        vote::Choice,
+    };
+
+    const VOTE_PLAN: &str = "d5bd73ca1b2cb59c44e9ca2e4aa3e4bc1a1aba2862fce19a9516e5041abfe92f";
+
+    /// a public vote paid by a single account, with a dummy signature
+    fn vote(choice: u8, value: u64) -> (Vec<u8>, String) {
+        let key = SecretKey::<Ed25519>::from_binary(&[1; 32]).unwrap();
+        let public = key.to_public();
+        let account = hex::encode(public.as_ref());
+
+        let vote_plan: VotePlanId = VOTE_PLAN.parse().unwrap();
+        let vote_cast = VoteCast::new(vote_plan, 2, Payload::public(Choice::new(choice)));
+        let input = Input::from_account_single(account::Identifier::from(public), Value(value));
+        let witness = Witness::Account(Signature::from_binary(&[0; 64]).unwrap());
+
+        let tx = TxBuilder::new()
+            .set_payload(&vote_cast)
+            .set_ios(&[input], &[])
+            .set_witnesses(&[witness])
+            .set_payload_auth(&());
+
+        (Fragment::VoteCast(tx).serialize_as_vec().unwrap(), account)
+    }
+
+    #[test]
+    fn decode_public_vote() {
+        let (bytes, account) = vote(1, 1_000);
+        let summary = decode(&bytes).unwrap();
+
+        assert_eq!(
+            summary.fragment_id,
+            Fragment::deserialize(bytes.as_slice())
+                .unwrap()
+                .hash()
+                .to_string()
+        );
+        assert_eq!(summary.vote_plan, VOTE_PLAN);
+        assert_eq!(summary.proposal_index, 2);
+        assert_eq!(summary.choice, Some(1));
+        // the vote has no output, the whole input pays the fee
+        assert_eq!(summary.fee, 1_000);
+        assert_eq!(summary.account, Some(account));
+    }
+
+    #[test]
+    fn reject_invalid_bytes() {
+        assert!(decode(&[]).is_err());
+        assert!(decode(&[0, 1, 2, 3]).is_err());
+
+        let (mut bytes, _) = vote(1, 1_000);
+        bytes.truncate(bytes.len() - 1);
+        assert!(decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn reject_other_fragments() {
+        let bytes = Fragment::Initial(ConfigParams::new())
+            .serialize_as_vec()
+            .unwrap();
+
+        assert_eq!(
+            decode(&bytes).unwrap_err(),
+            "the fragment is not a vote".to_owned()
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use iced_futures::futures;
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+use crate::node_client::{self, Request};
+
+/// delay between two queries of the fragment logs
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// the final status of a fragment
+#[derive(Debug, Clone)]
+pub enum Status {
+    InABlock {
+        /// `epoch.slot` of the block
+        date: String,
+        /// hash of the block
+        block: String,
+    },
+    Rejected {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum Progress {
+    /// the fragment is not in a block yet, polled for this long so far
+    Pending {
+        elapsed: Duration,
+    },
+    Finished(Status),
+    /// the fragment is still not in a block after the timeout
+    TimedOut(Duration),
+    Failed(node_client::Error),
+}
+
+/// an entry of the node's fragment logs
+#[derive(Debug, Deserialize)]
+struct FragmentLog {
+    fragment_id: String,
+    status: FragmentStatus,
+}
+
+#[derive(Debug, Deserialize)]
+enum FragmentStatus {
+    Pending,
+    Rejected {
+        reason: String,
+    },
+    InABlock {
+        date: String,
+        #[serde(default)]
+        block: String,
+    },
+}
+
+enum State {
+    Polling { started: Option<Instant> },
+    Finished,
+}
+
+/// poll the fragment logs of the node until the fragment is in a block or
+/// rejected, or until the timeout
+pub fn watch<T: ToString>(
+    url: T,
+    fragment_id: String,
+    timeout: Duration,
+    attempt: u32,
+) -> iced::Subscription<Progress> {
+    iced::Subscription::from_recipe(Watch {
+        request: Request::get(url),
+        fragment_id,
+        timeout,
+        attempt,
+    })
+}
+
+pub struct Watch {
+    request: Request,
+    fragment_id: String,
+    timeout: Duration,
+    /// a new attempt starts polling again
+    attempt: u32,
+}
+
+// Make sure iced can use our polling stream
+impl<H, I> iced_native::subscription::Recipe<H, I> for Watch
+where
+    H: std::hash::Hasher,
+{
+    type Output = Progress;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.request.hash(state);
+        self.fragment_id.hash(state);
+        self.timeout.hash(state);
+        self.attempt.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        poll(self.request, self.fragment_id, self.timeout)
+    }
+}
+
+fn poll(
+    request: Request,
+    fragment_id: String,
+    timeout: Duration,
+) -> futures::stream::BoxStream<'static, Progress> {
+    Box::pin(futures::stream::unfold(
+        State::Polling { started: None },
+        move |state| {
+            let request = request.clone();
+            let fragment_id = fragment_id.clone();
+
+            async move {
+                match state {
+                    State::Polling { started } => {
+                        let started = match started {
+                            Some(started) => {
+                                tokio::time::delay_for(POLL_INTERVAL).await;
+                                started
+                            }
+                            None => Instant::now(),
+                        };
+
+                        let status = match status(request, &fragment_id).await {
+                            Ok(status) => status,
+                            Err(error) => return Some((Progress::Failed(error), State::Finished)),
+                        };
+                        let elapsed = started.elapsed();
+
+                        match status {
+                            Some(FragmentStatus::InABlock { date, block }) => Some((
+                                Progress::Finished(Status::InABlock { date, block }),
+                                State::Finished,
+                            )),
+                            Some(FragmentStatus::Rejected { reason }) => Some((
+                                Progress::Finished(Status::Rejected { reason }),
+                                State::Finished,
+                            )),
+                            // the fragment may not be in the logs yet
+                            Some(FragmentStatus::Pending) | None if elapsed >= timeout => {
+                                Some((Progress::TimedOut(timeout), State::Finished))
+                            }
+                            Some(FragmentStatus::Pending) | None => Some((
+                                Progress::Pending { elapsed },
+                                State::Polling {
+                                    started: Some(started),
+                                },
+                            )),
+                        }
+                    }
+                    State::Finished => {
+                        // We do not let the stream die, as it would start
+                        // polling again
+                        let _: () = futures::future::pending().await;
+
+                        None
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// the status of the fragment in the node's logs, if it is there
+async fn status(
+    request: Request,
+    fragment_id: &str,
+) -> Result<Option<FragmentStatus>, node_client::Error> {
+    let url = request.url().to_owned();
+    let bytes = node_client::wait(node_client::fetch(request)).await?;
+
+    find(&bytes, fragment_id).map_err(|error| node_client::Error::Invalid {
+        url,
+        message: error.to_string(),
+    })
+}
+
+/// the status of the fragment in the fragment logs
+fn find(logs: &[u8], fragment_id: &str) -> Result<Option<FragmentStatus>, serde_json::Error> {
+    let logs: Vec<FragmentLog> = serde_json::from_slice(logs)?;
+
+    Ok(logs
+        .into_iter()
+        .find(|log| log.fragment_id == fragment_id)
+        .map(|log| log.status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGS: &str = r#"[
+        {
+            "fragment_id": "a1",
+            "received_from": "Rest",
+            "received_at": "2020-07-10T12:00:00.000000+00:00",
+            "last_updated_at": "2020-07-10T12:00:00.000000+00:00",
+            "status": "Pending"
+        },
+        {
+            "fragment_id": "b2",
+            "received_from": "Rest",
+            "received_at": "2020-07-10T12:00:00.000000+00:00",
+            "last_updated_at": "2020-07-10T12:00:02.000000+00:00",
+            "status": { "Rejected": { "reason": "account is not known" } }
+        },
+        {
+            "fragment_id": "c3",
+            "received_from": "Network",
+            "received_at": "2020-07-10T12:00:00.000000+00:00",
+            "last_updated_at": "2020-07-10T12:00:04.000000+00:00",
+            "status": { "InABlock": { "date": "12.345", "block": "d4" } }
+        },
+        {
+            "fragment_id": "e5",
+            "status": { "InABlock": { "date": "12.346" } }
+        }
+    ]"#;
+
+    #[test]
+    fn find_statuses() {
+        let status = |id| find(LOGS.as_bytes(), id).unwrap();
+
+        assert!(matches!(status("a1"), Some(FragmentStatus::Pending)));
+        match status("b2") {
+            Some(FragmentStatus::Rejected { reason }) => {
+                assert_eq!(reason, "account is not known")
+            }
+            status => panic!("unexpected status {:?}", status),
+        }
+        match status("c3") {
+            Some(FragmentStatus::InABlock { date, block }) => {
+                assert_eq!(date, "12.345");
+                assert_eq!(block, "d4");
+            }
+            status => panic!("unexpected status {:?}", status),
+        }
+        // older nodes do not give the block
+        match status("e5") {
+            Some(FragmentStatus::InABlock { date, block }) => {
+                assert_eq!(date, "12.346");
+                assert_eq!(block, "");
+            }
+            status => panic!("unexpected status {:?}", status),
+        }
+    }
+
+    #[test]
+    fn fragment_not_in_the_logs() {
+        assert!(find(LOGS.as_bytes(), "f6").unwrap().is_none());
+        assert!(find(b"[]", "a1").unwrap().is_none());
+    }
+
+    #[test]
+    fn reject_invalid_logs() {
+        assert!(find(b"", "a1").is_err());
+        assert!(find(b"{}", "a1").is_err());
+        assert!(find(br#"[{"fragment_id": "a1", "status": "Unknown"}]"#, "a1").is_err());
+    }
+}
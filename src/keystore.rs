@@ -0,0 +1,418 @@
+use cryptoxide::{chacha20poly1305::ChaCha20Poly1305, hmac::Hmac, pbkdf2::pbkdf2, sha2::Sha512};
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+use zeroize::{Zeroize as _, Zeroizing};
+
+use crate::{
+    mnemonic,
+    secret_key::{self, SecretKey},
+};
+
+/// version of the keystore file format
+const VERSION: u8 = 1;
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+const KEY_SIZE: usize = 32;
+const HEADER_SIZE: usize = 1 + SALT_SIZE + NONCE_SIZE + TAG_SIZE;
+/// PBKDF2 iterations deriving the encryption key from the password
+#[cfg(not(test))]
+const ITERATIONS: u32 = 100_000;
+/// the tests derive many keys, unoptimised
+#[cfg(test)]
+const ITERATIONS: u32 = 1_000;
+
+const MNEMONICS_TAG: u8 = 0;
+const SECRET_KEY_TAG: u8 = 1;
+
+/// the secret the wallet is recovered from
+pub enum Content {
+    /// the entropy of the mnemonics and their BIP39 passphrase, empty if
+    /// none. The wallet library derives the accounts from the mnemonics and
+    /// does not give out their keys, the entropy is kept instead of the words.
+    Mnemonics {
+        entropy: Vec<u8>,
+        passphrase: String,
+    },
+    /// the stake private key, in its extended form
+    SecretKey(SecretKey),
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    Random(getrandom::Error),
+    UnsupportedVersion(u8),
+    Invalid,
+    WrongPassword,
+}
+
+/// where the keystore is saved when no path is given
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("jorvote").join("keystore"))
+}
+
+/// encrypt the secret with a key derived from the password and write it to
+/// the keystore file, replacing the previous one
+pub fn save(path: &Path, password: &str, content: &Content) -> Result<(), Error> {
+    let mut salt = [0; SALT_SIZE];
+    let mut nonce = [0; NONCE_SIZE];
+    getrandom::getrandom(&mut salt).map_err(Error::Random)?;
+    getrandom::getrandom(&mut nonce).map_err(Error::Random)?;
+
+    let plaintext = Zeroizing::new(content.to_bytes());
+    let mut ciphertext = vec![0; plaintext.len()];
+    let mut tag = [0; TAG_SIZE];
+    let key = derive_key(password, &salt);
+    let mut cipher = ChaCha20Poly1305::new(&*key, &nonce, &[VERSION]);
+    cipher.encrypt(&plaintext, &mut ciphertext, &mut tag);
+
+    let mut file = Vec::with_capacity(HEADER_SIZE + ciphertext.len());
+    file.push(VERSION);
+    file.extend_from_slice(&salt);
+    file.extend_from_slice(&nonce);
+    file.extend_from_slice(&tag);
+    file.extend_from_slice(&ciphertext);
+
+    let io_error = |error| Error::Io {
+        path: path.to_owned(),
+        error,
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+
+    // the previous keystore is only replaced once the new one is written
+    let temporary = temporary_path(path);
+    let result = write_private(&temporary, &file).and_then(|()| fs::rename(&temporary, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result.map_err(io_error)
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// write the file, encrypted but still only readable by the user
+fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt as _;
+        options.mode(0o600);
+    }
+    let mut output = options.open(path)?;
+    #[cfg(unix)]
+    {
+        // the mode is only applied to new files, a previous attempt may have
+        // left this one
+        use std::os::unix::fs::PermissionsExt as _;
+        output.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    output.write_all(bytes)?;
+    output.sync_all()
+}
+
+/// read the keystore file and decrypt it with the password
+pub fn load(path: &Path, password: &str) -> Result<Content, Error> {
+    let file = fs::read(path).map_err(|error| Error::Io {
+        path: path.to_owned(),
+        error,
+    })?;
+    if file.len() < HEADER_SIZE {
+        return Err(Error::Invalid);
+    }
+    if file[0] != VERSION {
+        return Err(Error::UnsupportedVersion(file[0]));
+    }
+
+    let (salt, rest) = file[1..].split_at(SALT_SIZE);
+    let (nonce, rest) = rest.split_at(NONCE_SIZE);
+    let (tag, ciphertext) = rest.split_at(TAG_SIZE);
+
+    let mut plaintext = Zeroizing::new(vec![0; ciphertext.len()]);
+    let key = derive_key(password, salt);
+    let mut cipher = ChaCha20Poly1305::new(&*key, nonce, &[VERSION]);
+    if !cipher.decrypt(ciphertext, &mut plaintext, tag) {
+        return Err(Error::WrongPassword);
+    }
+
+    Content::from_bytes(&plaintext).ok_or(Error::Invalid)
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> Zeroizing<[u8; KEY_SIZE]> {
+    let mut key = Zeroizing::new([0; KEY_SIZE]);
+    let mut mac = Hmac::new(Sha512::new(), password.as_bytes());
+    pbkdf2(&mut mac, salt, ITERATIONS, &mut *key);
+    key
+}
+
+impl Content {
+    /// the secret as entered by the user: mnemonics with their passphrase or
+    /// a stake private key
+    pub fn parse(secret: &str, passphrase: &str) -> Result<Self, String> {
+        let secret = secret.trim();
+        if secret_key::is_secret_key(secret) {
+            secret_key::parse(secret)
+                .map(Content::SecretKey)
+                .map_err(|error| error.to_string())
+        } else {
+            let words: Vec<&str> = secret.split_whitespace().collect();
+            let phrase = Zeroizing::new(words.join(" "));
+            Ok(Content::Mnemonics {
+                entropy: mnemonic::entropy(&phrase)?.to_vec(),
+                passphrase: passphrase.to_owned(),
+            })
+        }
+    }
+
+    /// a tag then, for mnemonics, the entropy's length on a byte, the entropy
+    /// and the passphrase, or the secret key
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Content::Mnemonics {
+                entropy,
+                passphrase,
+            } => {
+                let mut bytes = Vec::with_capacity(2 + entropy.len() + passphrase.len());
+                bytes.push(MNEMONICS_TAG);
+                bytes.push(entropy.len() as u8);
+                bytes.extend_from_slice(entropy);
+                bytes.extend_from_slice(passphrase.as_bytes());
+                bytes
+            }
+            Content::SecretKey(key) => {
+                let mut bytes = Vec::with_capacity(1 + key.len());
+                bytes.push(SECRET_KEY_TAG);
+                bytes.extend_from_slice(key);
+                bytes
+            }
+        }
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (tag, rest) = bytes.split_first()?;
+        match *tag {
+            MNEMONICS_TAG => {
+                let (size, rest) = rest.split_first()?;
+                let size = *size as usize;
+                if rest.len() < size {
+                    return None;
+                }
+                let (entropy, passphrase) = rest.split_at(size);
+
+                Some(Content::Mnemonics {
+                    entropy: entropy.to_vec(),
+                    passphrase: String::from_utf8(passphrase.to_vec()).ok()?,
+                })
+            }
+            SECRET_KEY_TAG => {
+                let mut key: SecretKey = [0; 64];
+                if rest.len() != key.len() {
+                    return None;
+                }
+                key.copy_from_slice(rest);
+                Some(Content::SecretKey(key))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Drop for Content {
+    fn drop(&mut self) {
+        match self {
+            Content::Mnemonics {
+                entropy,
+                passphrase,
+            } => {
+                entropy.zeroize();
+                passphrase.zeroize();
+            }
+            Content::SecretKey(key) => key.zeroize(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io { path, error } => write!(f, "keystore {}: {}", path.display(), error),
+            Error::Random(error) => write!(f, "cannot generate the keystore salt: {}", error),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported keystore version {}", version)
+            }
+            Error::Invalid => write!(f, "invalid keystore file"),
+            Error::WrongPassword => write!(f, "wrong password"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    /// a keystore file removed at the end of the test
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            Self(std::env::temp_dir().join(format!(
+                "jorvote-keystore-{}-{}",
+                std::process::id(),
+                name
+            )))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn parse_secrets() {
+        match &Content::parse(&format!("  {}\n", PHRASE), "passphrase").unwrap() {
+            Content::Mnemonics {
+                entropy,
+                passphrase,
+            } => {
+                assert_eq!(entropy, &vec![0; 16]);
+                assert_eq!(passphrase, "passphrase");
+            }
+            Content::SecretKey(_) => panic!("mnemonics parsed as a secret key"),
+        }
+
+        let key = hex::encode([7; 32]);
+        assert!(matches!(
+            Content::parse(&key, "ignored").unwrap(),
+            Content::SecretKey(_)
+        ));
+
+        assert!(Content::parse(&["abandon"; 12].join(" "), "").is_err());
+        assert!(Content::parse(&hex::encode([7; 33]), "").is_err());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let path = TempPath::new("roundtrip");
+
+        save(
+            &path.0,
+            "password",
+            &Content::parse(PHRASE, "passphrase").unwrap(),
+        )
+        .unwrap();
+        match &load(&path.0, "password").unwrap() {
+            Content::Mnemonics {
+                entropy,
+                passphrase,
+            } => {
+                assert_eq!(*mnemonic::from_entropy(entropy).unwrap(), PHRASE);
+                assert_eq!(passphrase, "passphrase");
+            }
+            Content::SecretKey(_) => panic!("mnemonics loaded as a secret key"),
+        }
+
+        // the previous keystore is replaced
+        save(&path.0, "other password", &Content::SecretKey([0x40; 64])).unwrap();
+        match &load(&path.0, "other password").unwrap() {
+            Content::SecretKey(key) => assert_eq!(key.to_vec(), vec![0x40; 64]),
+            Content::Mnemonics { .. } => panic!("secret key loaded as mnemonics"),
+        }
+    }
+
+    #[test]
+    fn reject_wrong_password() {
+        let path = TempPath::new("wrong-password");
+
+        save(&path.0, "password", &Content::SecretKey([0x40; 64])).unwrap();
+        assert!(matches!(
+            load(&path.0, "Password"),
+            Err(Error::WrongPassword)
+        ));
+        assert!(matches!(load(&path.0, ""), Err(Error::WrongPassword)));
+    }
+
+    #[test]
+    fn reject_invalid_files() {
+        let path = TempPath::new("invalid");
+
+        assert!(matches!(load(&path.0, "password"), Err(Error::Io { .. })));
+
+        fs::write(&path.0, [VERSION; HEADER_SIZE - 1]).unwrap();
+        assert!(matches!(load(&path.0, "password"), Err(Error::Invalid)));
+
+        save(&path.0, "password", &Content::SecretKey([0x40; 64])).unwrap();
+        let mut file = fs::read(&path.0).unwrap();
+        file[0] = VERSION + 1;
+        fs::write(&path.0, &file).unwrap();
+        assert!(matches!(
+            load(&path.0, "password"),
+            Err(Error::UnsupportedVersion(version)) if version == VERSION + 1
+        ));
+
+        // a modified file does not decrypt
+        file[0] = VERSION;
+        let last = file.len() - 1;
+        file[last] ^= 1;
+        fs::write(&path.0, &file).unwrap();
+        assert!(matches!(
+            load(&path.0, "password"),
+            Err(Error::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn replace_the_previous_keystore() {
+        let path = TempPath::new("replace");
+
+        save(&path.0, "old", &Content::SecretKey([0x40; 64])).unwrap();
+        save(&path.0, "new", &Content::SecretKey([0x50; 64])).unwrap();
+
+        assert!(matches!(load(&path.0, "old"), Err(Error::WrongPassword)));
+        assert!(matches!(
+            load(&path.0, "new"),
+            Ok(Content::SecretKey(ref key)) if key[..] == [0x50; 64][..]
+        ));
+        assert!(!temporary_path(&path.0).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_readable_by_the_user() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let path = TempPath::new("mode");
+        fs::write(&path.0, []).unwrap();
+        fs::set_permissions(&path.0, fs::Permissions::from_mode(0o644)).unwrap();
+
+        save(&path.0, "password", &Content::SecretKey([0x40; 64])).unwrap();
+        let mode = fs::metadata(&path.0).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -1,24 +1,73 @@
 #![windows_subsystem = "windows"]
 
 use chain_addr::Discrimination;
+use chain_impl_mockchain::fee::LinearFee;
 use iced::{
-    button, executor, scrollable, text_input, Align, Application, Button, Column, Command,
-    Container, Element, HorizontalAlignment, Length, ProgressBar, Radio, Row, Scrollable, Settings,
-    Space, Subscription, Text, TextInput,
+    button, executor, scrollable, text_input, Align, Application, Button, Checkbox, Column,
+    Command, Container, Element, HorizontalAlignment, Length, ProgressBar, Radio, Row, Scrollable,
+    Settings, Space, Subscription, Text, TextInput,
 };
+use std::{
+    num::NonZeroU64,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+use structopt::StructOpt;
 use wallet_core as chain;
-
+use zeroize::{Zeroize as _, Zeroizing};
+
+mod arguments;
+mod block0;
+mod blockchain_time;
+mod cli;
+mod config;
+mod fragment;
+mod fragment_status;
+mod keystore;
+mod mnemonic;
+mod node_client;
+mod node_settings;
+mod secret_key;
 mod send_transaction;
+mod vote_plans;
 mod wallet_state;
 
+use arguments::{Arguments, Command as ArgumentsCommand};
+use blockchain_time::{VoteWindow, WindowStatus};
+use config::{Endpoints, PayloadType};
+use fragment::VoteSummary;
+use vote_plans::VotePlanStatus;
 use wallet_state::AccountState;
 
-const BLOCK0: &[u8] = include_bytes!("block0.bin");
-
 pub fn main() {
+    // the command line output and the help are printed on the console the app
+    // is run from
+    if std::env::args_os().len() > 1 {
+        attach_console();
+    }
     env_logger::init();
 
-    let mut settings = Settings::default();
+    let arguments = Arguments::from_args();
+
+    if let Some(ArgumentsCommand::Cli(cli)) = &arguments.command {
+        let result = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .map_err(Into::into)
+            .and_then(|mut runtime| cli::run(&arguments, cli, &mut runtime));
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // the app may not have a console, the startup errors are shown in its
+    // window
+    let flags = load_flags(&arguments).map_err(|error| error.to_string());
+
+    let mut settings = Settings::with_flags(flags);
 
     settings.window.size = (1024, 768);
     settings.window.resizable = true;
@@ -29,96 +78,555 @@ pub fn main() {
     Tour::run(settings);
 }
 
+/// the app has no console of its own on Windows, it uses the one of the
+/// process it was started from, if any
+#[cfg(windows)]
+fn attach_console() {
+    use winapi::um::wincon::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // fails when the app is not started from a console, there is nowhere to
+    // print to then
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+fn load_flags(arguments: &Arguments) -> Result<Flags, Box<dyn std::error::Error>> {
+    let config = config::Config::load(&arguments.config)?;
+    let endpoints = config.endpoints(arguments)?;
+    let block0 = config.block0(arguments)?;
+    let confirmation_timeout = config.confirmation_timeout(arguments)?;
+
+    Ok(Flags {
+        vote_plans: config.vote_plans,
+        live_settings: config.live_settings(arguments)?,
+        endpoints,
+        block0,
+        keystore: arguments.keystore.clone().or_else(keystore::default_path),
+        confirmation_timeout,
+    })
+}
+
 pub struct Tour {
     steps: Steps,
     scroll: scrollable::State,
     back_button: button::State,
     next_button: button::State,
     wallet: Wallet,
+    endpoints: Endpoints,
+    /// where the block0 is loaded from once the app is started
+    block0: block0::Source,
+    confirmation_timeout: Duration,
+    /// why the app cannot start, the configuration is only read once
+    failed: Option<String>,
+}
+
+/// what the app needs to start, loaded from the configuration
+pub struct Flags {
+    /// the configured vote plans, completing the ones active on the node
+    vote_plans: Vec<config::VotePlan>,
+    /// the settings of the node are used instead of the block0's, once
+    /// fetched
+    live_settings: bool,
+    endpoints: Endpoints,
+    block0: block0::Source,
+    /// where the wallet's secret is saved, if it can be
+    keystore: Option<PathBuf>,
+    /// how long to wait for a sent vote to be in a block
+    confirmation_timeout: Duration,
 }
 
 pub struct Wallet {
     wallet: Option<chain::Wallet>,
-    id: Option<String>,
+    accounts: Vec<Account>,
+    /// the voting accounts are the ones derived from the UTxO wallet's
+    /// mnemonics, and not an account imported from its private key
+    utxo_account: bool,
     settings: Option<chain::Settings>,
-    proposal: chain::Proposal,
-    vote: Option<Box<[u8]>>,
+    block0: Vec<u8>,
+    proposals: Vec<Proposal>,
+    /// changes every time the proposals are replaced, the choices made on the
+    /// previous ones do not apply to them
+    proposals_version: u32,
+    /// the configured vote plans, completing the ones active on the node
+    vote_plans: Vec<config::VotePlan>,
+    /// the blockchain time the vote windows are checked with, not known from
+    /// the command line
+    clock: Option<blockchain_time::Clock>,
+    /// the settings of the node are asked for instead of the block0's
+    use_live_settings: bool,
+    /// the settings of the node the votes are signed with instead of the
+    /// block0's, once fetched
+    live_settings: Option<node_settings::NodeSettings>,
+    /// how the settings of the node differ from the block0's
+    settings_changes: Vec<String>,
+    votes: Vec<Vote>,
+    /// the choices no vote could be signed for
+    skipped: Vec<Skipped>,
+}
+
+/// one of the accounts of the recovered wallet
+pub struct Account {
+    pub id: String,
+    /// value and spending counter retrieved from the node
+    pub state: Option<(chain::Value, u32)>,
+    /// the account is used to vote
+    pub selected: bool,
+}
+
+/// a proposal the app can vote on, as described in the vote plan configuration
+pub struct Proposal {
+    pub title: String,
+    pub labels: Vec<String>,
+    pub options: chain::Options,
+    /// minimum value, in lovelace, of the accounts voting on the proposal
+    pub voting_power_threshold: u64,
+    pub payload_type: PayloadType,
+    /// id of the vote plan of the proposal
+    pub vote_plan: String,
+    /// when the votes are accepted, known for the vote plans active on the node
+    pub window: Option<VoteWindow>,
+    /// the proposal is shown in the vote step
+    pub selected: bool,
+    pub proposal: chain::Proposal,
+}
+
+impl Proposal {
+    /// every choice accepted by the proposal, with its label
+    pub fn choices(&self) -> impl Iterator<Item = (Choice, String)> + '_ {
+        self.options.choice_range().clone().map(move |index| {
+            let label = self
+                .labels
+                .get(index as usize)
+                .cloned()
+                .unwrap_or_else(|| format!("Option {}", index));
+            (Choice(index), label)
+        })
+    }
+
+    /// the account's voting power reaches the proposal's threshold
+    pub fn can_vote(&self, account: &Account) -> bool {
+        account
+            .voting_power()
+            .map(|power| power >= self.voting_power_threshold)
+            .unwrap_or(false)
+    }
+}
+
+impl Account {
+    /// the value of the account, in lovelace, once retrieved from the node
+    pub fn voting_power(&self) -> Option<u64> {
+        self.state.map(|(value, _)| value.0)
+    }
+}
+
+/// a vote plan active on the node
+#[derive(Debug, Clone)]
+pub struct ActivePlan {
+    pub status: VotePlanStatus,
+    /// why the plan's proposals cannot be voted on
+    pub error: Option<String>,
+}
+
+/// a signed vote fragment, ready to be sent to the blockchain
+pub struct Vote {
+    /// index of the account signing the vote in the wallet's accounts
+    pub account: usize,
+    /// index of the voted proposal in the wallet's proposals
+    pub proposal: usize,
+    /// the choice, kept to show it back when the vote is private
+    pub choice: Choice,
+    /// spending counter of the account used to sign the vote
+    pub counter: u32,
+    pub fragment: Box<[u8]>,
+}
+
+/// a choice of the user no vote was signed for
+pub struct Skipped {
+    /// index of the account in the wallet's accounts
+    pub account: usize,
+    /// index of the proposal in the wallet's proposals
+    pub proposal: usize,
+    pub reason: String,
 }
 
 impl Wallet {
-    pub fn new() -> Self {
-        let id = "d5bd73ca1b2cb59c44e9ca2e4aa3e4bc1a1aba2862fce19a9516e5041abfe92f"
-            .parse()
-            .unwrap();
+    pub fn new(
+        proposals: Vec<Proposal>,
+        vote_plans: Vec<config::VotePlan>,
+        block0: Vec<u8>,
+    ) -> Self {
         Self {
             wallet: None,
-            id: None,
+            accounts: Vec::new(),
+            utxo_account: true,
             settings: None,
-            proposal: chain::Proposal::new(
-                id,
-                chain::PayloadType::Public,
-                0,
-                chain::Options::new_length(3).unwrap(),
-            ),
-            vote: None,
+            block0,
+            proposals,
+            proposals_version: 0,
+            vote_plans,
+            clock: None,
+            use_live_settings: false,
+            live_settings: None,
+            settings_changes: Vec::new(),
+            votes: Vec::new(),
+            skipped: Vec::new(),
         }
     }
 
-    pub fn recover(&mut self, mnemonics: &str) -> Result<(), chain::Error> {
-        let mut wallet = chain::Wallet::recover(mnemonics, &[])?;
-        let settings = wallet.retrieve_funds(BLOCK0)?;
+    /// recover the wallet from its mnemonics or from the stake private key.
+    ///
+    /// The passphrase of the mnemonics is their optional BIP39 password.
+    pub fn recover(&mut self, secret: &keystore::Content) -> Result<(), String> {
+        let (mut wallet, utxo_account) = match secret {
+            keystore::Content::Mnemonics {
+                entropy,
+                passphrase,
+            } => {
+                let mnemonics = mnemonic::from_entropy(entropy)?;
+                let wallet = chain::Wallet::recover(&mnemonics, passphrase.as_bytes())
+                    .map_err(|error| error.to_string())?;
+                (wallet, true)
+            }
+            keystore::Content::SecretKey(key) => {
+                let wallet = chain::Wallet::recover_free_keys(key, &[])
+                    .map_err(|error| error.to_string())?;
+                (wallet, false)
+            }
+        };
+        let mut settings = wallet
+            .retrieve_funds(&self.block0)
+            .map_err(|error| error.to_string())?;
+        self.settings_changes = match &self.live_settings {
+            Some(live_settings) => live_settings.apply(&mut settings),
+            None => Vec::new(),
+        };
 
-        self.id = wallet.utxo_account_id().get(0).map(|id| id.to_string());
+        let ids = if utxo_account {
+            wallet
+                .utxo_account_id()
+                .iter()
+                .map(|id| id.to_string())
+                .collect()
+        } else {
+            vec![wallet.id().to_string()]
+        };
+        // the first account votes unless others are picked
+        self.accounts = ids
+            .into_iter()
+            .enumerate()
+            .map(|(index, id)| Account {
+                id,
+                state: None,
+                selected: index == 0,
+            })
+            .collect();
+        self.utxo_account = utxo_account;
         self.wallet = Some(wallet);
         self.settings = Some(settings);
 
         Ok(())
     }
 
-    pub fn set_state(&mut self, value: chain::Value, counter: u32) {
-        if let Some(wallet) = self.wallet.as_mut() {
-            if let Some(id) = &self.id {
-                let id = id.parse().unwrap();
-                wallet.utxo_account_id_set_state(&id, value, counter)
+    /// the proposal is selected and one of the selected accounts has enough
+    /// voting power for it
+    pub fn can_vote(&self, proposal: usize) -> bool {
+        self.proposals[proposal].selected
+            && self
+                .accounts
+                .iter()
+                .filter(|account| account.selected)
+                .any(|account| self.proposals[proposal].can_vote(account))
+    }
+
+    /// replace the proposals with the ones of the vote plans active on the
+    /// node, completed with the configured vote plans
+    pub fn set_vote_plans(&mut self, statuses: Vec<VotePlanStatus>) -> Vec<ActivePlan> {
+        let mut proposals = Vec::new();
+        let mut plans = Vec::new();
+
+        for status in statuses {
+            let configured = self.vote_plans.iter().find(|plan| plan.id == status.id);
+            let window = VoteWindow {
+                start: status.vote_start,
+                end: status.vote_end,
+            };
+            let error = match config::VotePlan::active(&status, configured).proposals() {
+                Ok(plan_proposals) => {
+                    proposals.extend(plan_proposals.into_iter().map(|proposal| Proposal {
+                        window: Some(window),
+                        ..proposal
+                    }));
+                    None
+                }
+                Err(error) => Some(error.to_string()),
+            };
+            plans.push(ActivePlan { status, error });
+        }
+
+        self.proposals = proposals;
+        self.proposals_version += 1;
+        plans
+    }
+
+    /// whether the proposal accepts votes now, `None` if it is not known
+    pub fn window_status(&self, proposal: usize) -> Option<WindowStatus> {
+        let clock = self.clock.as_ref()?;
+        let window = self.proposals[proposal].window?;
+
+        Some(clock.status(window, clock.now()))
+    }
+
+    /// the block0 the wallet is recovered from, and the blockchain time it
+    /// sets
+    pub fn set_block0(&mut self, block0: Vec<u8>) -> Result<(), String> {
+        let clock =
+            blockchain_time::Clock::from_block0(&block0).map_err(|error| error.to_string())?;
+
+        self.clock = Some(clock);
+        self.block0 = block0;
+        Ok(())
+    }
+
+    /// follow the node's clock and use its settings if asked, after checking
+    /// it runs the blockchain of the block0
+    pub fn set_node_settings(
+        &mut self,
+        settings: &node_settings::NodeSettings,
+    ) -> Result<(), node_settings::Error> {
+        settings.verify(&self.block0)?;
+
+        // the recovered wallet's settings are the block0's until the node's
+        // are applied, only once
+        if self.use_live_settings && self.live_settings.is_none() {
+            if let Some(wallet_settings) = &mut self.settings {
+                self.settings_changes = settings.apply(wallet_settings);
+            }
+            self.live_settings = Some(settings.clone());
+        }
+
+        match (&mut self.clock, settings.curr_slot_start_time) {
+            (Some(clock), Some(slot_start)) => {
+                clock.sync(slot_start.into(), SystemTime::now());
+                Ok(())
             }
+            (Some(_), None) => Err(node_settings::Error::NoTime),
+            (None, _) => Ok(()),
         }
     }
 
-    pub fn make_choice(&mut self, choice: Choice) {
-        self.vote = self
-            .wallet
-            .as_mut()
-            .unwrap()
-            .vote(
-                self.settings.clone().unwrap(),
-                &self.proposal,
-                choice.into(),
-                true,
-            )
-            .ok();
-        dbg!(&self.vote);
+    /// the proposal accepts votes now, or it is not known
+    pub fn is_open(&self, proposal: usize) -> bool {
+        self.window_status(proposal)
+            .map(WindowStatus::is_open)
+            .unwrap_or(true)
     }
-}
 
-impl Default for Wallet {
-    fn default() -> Self {
-        Self::new()
+    /// the first account of the wallet, the one used when none is picked
+    pub fn id(&self) -> Option<&str> {
+        self.accounts.first().map(|account| account.id.as_str())
+    }
+
+    /// record the account's state retrieved from the node
+    pub fn set_state(
+        &mut self,
+        account: usize,
+        value: chain::Value,
+        counter: u32,
+    ) -> Result<(), String> {
+        self.sign_with(account, value, counter)?;
+        self.accounts[account].state = Some((value, counter));
+        Ok(())
+    }
+
+    /// give the account's value and spending counter to the wallet, which
+    /// signs the next votes with the account whose state it was given last
+    fn sign_with(
+        &mut self,
+        account: usize,
+        value: chain::Value,
+        counter: u32,
+    ) -> Result<(), String> {
+        let wallet = match self.wallet.as_mut() {
+            Some(wallet) => wallet,
+            None => return Ok(()),
+        };
+        if !self.utxo_account {
+            wallet.set_state(value, counter);
+        } else {
+            let id = &self.accounts[account].id;
+            let id = id
+                .parse()
+                .map_err(|error| format!("invalid account id {}: {}", id, error))?;
+            wallet.utxo_account_id_set_state(&id, value, counter);
+        }
+        Ok(())
+    }
+
+    /// the fee of a vote, in lovelace, from the fee settings of the blockchain
+    pub fn vote_fee(&self) -> Option<u64> {
+        self.settings
+            .as_ref()
+            .map(|settings| vote_fee(&settings.fees))
+    }
+
+    /// the selected accounts whose value does not cover the fees of the votes
+    /// they would sign, with these fees
+    pub fn unfunded(&self, choices: &[Option<Choice>]) -> Vec<(usize, u64)> {
+        match self.vote_fee() {
+            Some(fee) => self.unfunded_with(choices, fee),
+            None => Vec::new(),
+        }
+    }
+
+    fn unfunded_with(&self, choices: &[Option<Choice>], fee: u64) -> Vec<(usize, u64)> {
+        self.accounts
+            .iter()
+            .enumerate()
+            .filter(|(_, account)| account.selected)
+            .filter_map(|(index, account)| {
+                let votes = choices
+                    .iter()
+                    .zip(self.proposals.iter())
+                    .filter(|(choice, proposal)| choice.is_some() && proposal.can_vote(account))
+                    .count() as u64;
+                let fees = votes * fee;
+
+                if account.voting_power().unwrap_or(0) < fees {
+                    Some((index, fees))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// build one vote per proposal that has a choice for every selected
+    /// account, `choices` being indexed like the wallet's proposals. The
+    /// votes the accounts cannot sign, below the proposal's voting power
+    /// threshold or failing, are listed in `skipped`.
+    pub fn make_choices(&mut self, choices: &[Option<Choice>]) {
+        let mut votes = Vec::new();
+        let mut skipped = Vec::new();
+
+        for account in 0..self.accounts.len() {
+            if !self.accounts[account].selected {
+                continue;
+            }
+
+            // every vote uses the next spending counter of the account, from
+            // the retrieved state
+            let (value, mut counter) = match self.accounts[account].state {
+                Some(state) => state,
+                None => continue,
+            };
+
+            let settings = self.settings.clone().unwrap();
+            for (proposal, choice) in choices.iter().enumerate() {
+                let choice = match choice {
+                    Some(choice) => *choice,
+                    None => continue,
+                };
+                let mut skip = |reason: String| {
+                    skipped.push(Skipped {
+                        account,
+                        proposal,
+                        reason,
+                    })
+                };
+                if !self.proposals[proposal].can_vote(&self.accounts[account]) {
+                    skip(format!(
+                        "the account has a voting power of {}, {} are needed to vote on the proposal",
+                        format_value(value.0),
+                        format_value(self.proposals[proposal].voting_power_threshold)
+                    ));
+                    continue;
+                }
+
+                // the account and its counter are given again before every
+                // vote, the wallet not being told which account signs
+                if let Err(error) = self.sign_with(account, value, counter) {
+                    skip(format!("cannot sign the vote: {}", error));
+                    continue;
+                }
+                let fragment = match self.wallet.as_mut().unwrap().vote(
+                    settings.clone(),
+                    &self.proposals[proposal].proposal,
+                    choice.into(),
+                    self.utxo_account,
+                ) {
+                    Ok(fragment) => fragment,
+                    Err(error) => {
+                        skip(format!("cannot sign the vote: {}", error));
+                        continue;
+                    }
+                };
+                // the vote must be paid by the account it is meant for
+                let signer = fragment::decode(&fragment).map(|summary| summary.account);
+                match signer {
+                    Ok(Some(signer)) if signer.eq_ignore_ascii_case(&self.accounts[account].id) => {
+                        votes.push(Vote {
+                            account,
+                            proposal,
+                            choice,
+                            counter,
+                            fragment,
+                        });
+                        counter += 1;
+                    }
+                    Ok(signer) => skip(format!(
+                        "the vote is signed by another account: {}",
+                        signer.unwrap_or_default()
+                    )),
+                    Err(error) => skip(format!("invalid vote: {}", error)),
+                }
+            }
+        }
+
+        self.votes = votes;
+        self.skipped = skipped;
     }
 }
 
 impl Application for Tour {
     type Executor = executor::Default;
     type Message = Message;
-    type Flags = ();
+    type Flags = Result<Flags, String>;
+
+    fn new(flags: Result<Flags, String>) -> (Tour, Command<Message>) {
+        let (flags, failed) = match flags {
+            Ok(flags) => (flags, None),
+            Err(error) => (
+                Flags {
+                    vote_plans: Vec::new(),
+                    live_settings: false,
+                    endpoints: Endpoints::default(),
+                    block0: block0::Source::default(),
+                    keystore: None,
+                    confirmation_timeout: Duration::default(),
+                },
+                Some(error),
+            ),
+        };
+        // the proposals are the ones of the vote plans active on the node,
+        // known once they are fetched, and the block0 is loaded once started
+        let mut wallet = Wallet::new(Vec::new(), flags.vote_plans, Vec::new());
+        wallet.use_live_settings = flags.live_settings;
 
-    fn new(_flags: ()) -> (Tour, Command<Message>) {
         (
             Tour {
-                steps: Steps::new(),
+                steps: Steps::new(flags.keystore),
                 scroll: scrollable::State::new(),
                 back_button: button::State::new(),
                 next_button: button::State::new(),
-                wallet: Wallet::new(),
+                wallet,
+                endpoints: flags.endpoints,
+                block0: flags.block0,
+                confirmation_timeout: flags.confirmation_timeout,
+                failed,
             },
             Command::none(),
         )
@@ -134,7 +642,7 @@ impl Application for Tour {
                 self.steps.go_back();
             }
             Message::NextPressed => {
-                self.steps.advance();
+                self.steps.advance(&mut self.wallet);
             }
             Message::StepMessage(step_msg) => self.steps.update(step_msg, &mut self.wallet),
             Message::OpenLink(link) => {
@@ -147,31 +655,96 @@ impl Application for Tour {
     }
 
     fn subscription(&self) -> Subscription<Message> {
+        if self.failed.is_some() {
+            return Subscription::none();
+        }
+
         match self.steps.current() {
-            Step::LoadState {
+            Step::Welcome {
                 loaded: None,
-                progressed: _,
+                attempt,
+                ..
+            } => block0::query(self.block0.clone(), self.endpoints.clone(), *attempt)
+                .map(|loaded| StepMessage::Block0 { loaded })
+                .map(Message::StepMessage),
+            Step::LoadState {
+                loaded, attempt, ..
             } => {
-                dbg!(self.wallet.id.clone().unwrap());
-                let url = format!(
-                    "https://explorer.incentivized-testnet.iohkdev.io/api/v0/account/{}",
-                    self.wallet.id.clone().unwrap(),
-                );
-
-                wallet_state::query(url)
-                    .map(|progress| StepMessage::State { progress })
-                    .map(Message::StepMessage)
+                // the accounts are queried one after the other
+                if let Some(index) = loaded.iter().position(Option::is_none) {
+                    let url = self.endpoints.account(&self.wallet.accounts[index].id);
+
+                    node_client::query(url, *attempt)
+                        .map(|progress| StepMessage::State { progress })
+                        .map(Message::StepMessage)
+                } else {
+                    Subscription::none()
+                }
+            }
+            Step::VotePlans {
+                loaded,
+                synced,
+                attempt,
+                ..
+            } => {
+                let plans = if loaded.is_none() {
+                    node_client::query(self.endpoints.vote_plans(), *attempt)
+                        .map(|progress| StepMessage::VotePlans { progress })
+                        .map(Message::StepMessage)
+                } else {
+                    Subscription::none()
+                };
+
+                // the vote windows are checked with the node's time
+                let settings = if synced.is_none() {
+                    node_client::query(self.endpoints.settings(), *attempt)
+                        .map(|progress| StepMessage::Settings { progress })
+                        .map(Message::StepMessage)
+                } else {
+                    Subscription::none()
+                };
+
+                Subscription::batch(vec![plans, settings])
             }
             Step::WaitConfirmation {
-                loaded: None,
-                progressed: _,
-                open_button: _,
+                submissions,
+                attempt,
+                ..
             } => {
-                let url = "https://explorer.incentivized-testnet.iohkdev.io/api/v0/message".to_owned();
-                let body = self.wallet.vote.clone().unwrap_or_default();
-
-                send_transaction::post(url, body)
-                    .map(|progress| StepMessage::Transaction { progress })
+                // the votes are sent one after the other so the node receives
+                // them in the order of their spending counters
+                let send = if let Some(index) = submissions.iter().position(|s| s.loaded.is_none())
+                {
+                    let url = self.endpoints.fragment.clone();
+                    let body = self.wallet.votes[index].fragment.clone();
+
+                    send_transaction::post(url, body, *attempt)
+                        .map(|progress| StepMessage::Transaction { progress })
+                        .map(Message::StepMessage)
+                } else {
+                    Subscription::none()
+                };
+
+                // the sent votes are followed one after the other too, the
+                // following ones are usually in the same block
+                let watch = if let Some(submission) = submissions.iter().find(|s| s.is_pending()) {
+                    let url = self.endpoints.fragment_logs();
+                    let id = submission.fragment_id().unwrap_or_default().to_owned();
+
+                    fragment_status::watch(url, id, self.confirmation_timeout, *attempt)
+                        .map(|progress| StepMessage::Status { progress })
+                        .map(Message::StepMessage)
+                } else {
+                    Subscription::none()
+                };
+
+                Subscription::batch(vec![send, watch])
+            }
+            // the vote windows open and close while the user chooses and
+            // reviews the votes
+            Step::Vote { .. } | Step::Confirm { .. } => {
+                blockchain_time::every(Duration::from_secs(1))
+                    .map(|()| StepMessage::Tick)
                     .map(Message::StepMessage)
             }
             _ => Subscription::none(),
@@ -184,9 +757,31 @@ impl Application for Tour {
             scroll,
             back_button,
             next_button,
+            wallet,
+            endpoints,
+            failed,
             ..
         } = self;
 
+        if let Some(error) = failed {
+            let content = Column::new()
+                .max_width(800)
+                .spacing(20)
+                .padding(20)
+                .push(Text::new("Cannot start").size(50))
+                .push(Text::new(error.to_owned()))
+                .push(Text::new(
+                    "Fix the configuration or the command line and start the app again.",
+                ));
+
+            return Container::new(content)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
         let mut controls = Row::new();
 
         if steps.has_previous() {
@@ -199,7 +794,7 @@ impl Application for Tour {
 
         controls = controls.push(Space::with_width(Length::Fill));
 
-        if steps.can_continue() {
+        if steps.can_continue(wallet) {
             controls = controls.push(
                 button(next_button, "Next")
                     .on_press(Message::NextPressed)
@@ -211,7 +806,7 @@ impl Application for Tour {
             .max_width(800)
             .spacing(5)
             .padding(5)
-            .push(steps.view())
+            .push(steps.view(wallet, endpoints))
             .push(controls)
             .into();
 
@@ -239,25 +834,58 @@ struct Steps {
 }
 
 impl Steps {
-    fn new() -> Steps {
+    fn new(keystore: Option<PathBuf>) -> Steps {
         Steps {
             steps: vec![
-                Step::Welcome,
+                Step::Welcome {
+                    loaded: None,
+                    attempt: 0,
+                    retry_button: button::State::new(),
+                },
                 Step::EnterKey {
-                    key: String::new(),
+                    key: Zeroizing::new(String::new()),
+                    passphrase: Zeroizing::new(String::new()),
+                    reveal: false,
                     retrieved: false,
                     state: text_input::State::new(),
+                    passphrase_state: text_input::State::new(),
+                    reveal_button: button::State::new(),
+                    mnemonic: mnemonic::Status::Empty,
+                    suggestion_buttons: vec![button::State::new(); SUGGESTIONS],
+                    keystore: KeystoreInput::new(keystore),
                     error: None,
+                    preview: None,
+                    preview_button: button::State::new(),
                 },
-                Step::LoadState {
+                // the proposals are known before the accounts are loaded, to
+                // tell which ones their voting power is too low for
+                Step::VotePlans {
                     loaded: None,
-                    progressed: 0.0,
+                    synced: None,
+                    progressed: Transfer::default(),
+                    attempt: 0,
+                    retry_button: button::State::new(),
+                },
+                Step::LoadState {
+                    loaded: Vec::new(),
+                    deselected: Vec::new(),
+                    picked: None,
+                    progressed: Transfer::default(),
+                    attempt: 0,
+                    retry_button: button::State::new(),
+                },
+                Step::Vote {
+                    choices: Vec::new(),
+                    proposals_version: 0,
+                },
+                Step::Confirm {
+                    summaries: Vec::new(),
                 },
-                Step::Vote { choice: None },
                 Step::WaitConfirmation {
-                    loaded: None,
-                    progressed: 0.0,
-                    open_button: button::State::new(),
+                    submissions: Vec::new(),
+                    progressed: Transfer::default(),
+                    attempt: 0,
+                    retry_button: button::State::new(),
                 },
                 Step::End,
             ],
@@ -273,13 +901,14 @@ impl Steps {
         self.steps.get(self.current).expect("cannot overflow")
     }
 
-    fn view(&mut self) -> Element<Message> {
-        self.steps[self.current].view()
+    fn view<'a>(&'a mut self, wallet: &'a Wallet, endpoints: &Endpoints) -> Element<'a, Message> {
+        self.steps[self.current].view(wallet, endpoints)
     }
 
-    fn advance(&mut self) {
-        if self.can_continue() {
+    fn advance(&mut self, wallet: &mut Wallet) {
+        if self.can_continue(wallet) && self.steps[self.current].leave(wallet) {
             self.current += 1;
+            self.steps[self.current].enter(wallet);
         }
     }
 
@@ -290,11 +919,11 @@ impl Steps {
     }
 
     fn has_previous(&self) -> bool {
-        self.current > 0
+        self.current > 0 && self.steps[self.current].can_go_back()
     }
 
-    fn can_continue(&self) -> bool {
-        self.current + 1 < self.steps.len() && self.steps[self.current].can_continue()
+    fn can_continue(&self, wallet: &Wallet) -> bool {
+        self.current + 1 < self.steps.len() && self.steps[self.current].can_continue(wallet)
     }
 
     fn title(&self) -> &str {
@@ -302,152 +931,881 @@ impl Steps {
     }
 }
 
+/// how many words of the BIP39 list are proposed to complete the word being
+/// typed
+const SUGGESTIONS: usize = 6;
+
 #[allow(clippy::large_enum_variant)]
 enum Step {
-    Welcome,
+    Welcome {
+        /// the block0 of the network, loaded from a file or the node
+        loaded: Option<Result<(), String>>,
+        /// how many times the user asked to retry, the block0 is loaded again
+        /// when it changes
+        attempt: u32,
+        retry_button: button::State,
+    },
     EnterKey {
-        key: String,
+        /// the mnemonics or private key, wiped once the wallet is recovered
+        key: Zeroizing<String>,
+        passphrase: Zeroizing<String>,
+        /// show the secret instead of masking it
+        reveal: bool,
         retrieved: bool,
         state: text_input::State,
-        error: Option<chain::Error>,
+        passphrase_state: text_input::State,
+        reveal_button: button::State,
+        mnemonic: mnemonic::Status,
+        suggestion_buttons: Vec<button::State>,
+        keystore: KeystoreInput,
+        error: Option<String>,
+        /// the id of the account the secret recovers, checked before moving
+        /// on
+        preview: Option<String>,
+        preview_button: button::State,
     },
     LoadState {
-        loaded: Option<Result<AccountState, String>>,
-        progressed: f32,
+        /// the state of every account of the wallet
+        loaded: Vec<Option<Result<AccountState, String>>>,
+        /// the accounts deselected because their state could not be loaded,
+        /// selected again once it is
+        deselected: Vec<bool>,
+        /// the account selected in place of the deselected ones, deselected
+        /// again once one of them is loaded
+        picked: Option<usize>,
+        progressed: Transfer,
+        /// how many times the user asked to retry, the queries are restarted
+        /// when it changes
+        attempt: u32,
+        retry_button: button::State,
+    },
+    VotePlans {
+        /// the vote plans active on the node
+        loaded: Option<Result<Vec<ActivePlan>, String>>,
+        /// the clock follows the node's, or why it cannot
+        synced: Option<Result<(), node_settings::Error>>,
+        progressed: Transfer,
+        /// how many times the user asked to retry, the query is restarted
+        /// when it changes
+        attempt: u32,
+        retry_button: button::State,
     },
     Vote {
-        choice: Option<Choice>,
+        choices: Vec<Option<Choice>>,
+        /// the version of the wallet's proposals the choices were made on
+        proposals_version: u32,
+    },
+    Confirm {
+        summaries: Vec<Result<VoteSummary, String>>,
     },
     WaitConfirmation {
-        loaded: Option<Result<String, String>>,
-        progressed: f32,
-        open_button: button::State,
+        submissions: Vec<Submission>,
+        progressed: Transfer,
+        /// how many times the user asked to retry, the submissions are
+        /// restarted when it changes
+        attempt: u32,
+        retry_button: button::State,
     },
     End,
 }
 
+/// saving the wallet's secret in the encrypted keystore, or unlocking the one
+/// saved in a previous session
+struct KeystoreInput {
+    path: Option<PathBuf>,
+    /// a keystore file exists and can be unlocked
+    saved: bool,
+    /// save the secret in the keystore when moving on from the step
+    remember: bool,
+    password: Zeroizing<String>,
+    password_state: text_input::State,
+    unlock_button: button::State,
+    status: Option<Result<String, String>>,
+}
+
+impl KeystoreInput {
+    fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            saved: path.as_ref().map(|path| path.is_file()).unwrap_or(false),
+            path,
+            remember: false,
+            password: Zeroizing::new(String::new()),
+            password_state: text_input::State::new(),
+            unlock_button: button::State::new(),
+            status: None,
+        }
+    }
+}
+
+/// sending status of one of the wallet's votes
+struct Submission {
+    account: usize,
+    proposal: usize,
+    /// the fragment id once sent
+    loaded: Option<Result<String, String>>,
+    /// the final status of the sent fragment
+    status: Option<Result<fragment_status::Status, String>>,
+    /// how long the sent fragment has been waiting for a block
+    waited: Duration,
+    open_button: button::State,
+}
+
+impl Submission {
+    fn fragment_id(&self) -> Option<&str> {
+        match &self.loaded {
+            Some(Ok(id)) => Some(id.trim()),
+            _ => None,
+        }
+    }
+
+    /// the fragment was sent but its final status is not known yet
+    fn is_pending(&self) -> bool {
+        self.fragment_id().is_some() && self.status.is_none()
+    }
+}
+
+/// the vote is not sent, and neither are the following votes of its account:
+/// they use the next spending counters and would be rejected without it
+fn fail(submissions: &mut [Submission], index: usize, error: String) {
+    let account = submissions[index].account;
+    submissions[index].loaded = Some(Err(error));
+
+    for submission in submissions
+        .iter_mut()
+        .skip(index + 1)
+        .filter(|s| s.account == account && s.loaded.is_none())
+    {
+        submission.loaded = Some(Err("Not sent: a previous vote failed".to_owned()));
+    }
+}
+
+/// the votes not sent yet are checked against their vote window again, the
+/// node would reject the ones it closed for
+fn skip_closed(submissions: &mut [Submission], wallet: &Wallet) {
+    for index in 0..submissions.len() {
+        let submission = &submissions[index];
+        if submission.loaded.is_none() && !wallet.is_open(submission.proposal) {
+            fail(
+                submissions,
+                index,
+                "Not sent: the vote plan no longer accepts votes".to_owned(),
+            );
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum StepMessage {
     ChangeKey(String),
+    ChangePassphrase(String),
+    Autocomplete(&'static str),
+    ToggleReveal,
+    PreviewAccount,
+    ChangeKeystorePassword(String),
+    ToggleRemember(bool),
+    UnlockKeystore,
+    Block0 {
+        loaded: Result<Vec<u8>, String>,
+    },
     State {
         progress: wallet_state::Progress,
     },
     Transaction {
         progress: send_transaction::Progress,
     },
-    SelectVote(Choice),
+    Status {
+        progress: fragment_status::Progress,
+    },
+    VotePlans {
+        progress: vote_plans::Progress,
+    },
+    Settings {
+        progress: node_settings::Progress,
+    },
+    Retry,
+    SelectAccount(usize, bool),
+    SelectProposal(usize, bool),
+    Tick,
+    SelectVote(usize, Choice),
 }
 
 impl<'a> Step {
+    /// called when the wizard moves forward to this step
+    fn enter(&mut self, wallet: &mut Wallet) {
+        match self {
+            Step::LoadState {
+                loaded,
+                deselected,
+                picked,
+                progressed,
+                ..
+            } => {
+                *loaded = vec![None; wallet.accounts.len()];
+                *deselected = vec![false; wallet.accounts.len()];
+                *picked = None;
+                *progressed = Transfer::default();
+            }
+            Step::VotePlans { loaded, synced, .. } => {
+                // the vote plans do not depend on the wallet, they are only
+                // queried again if they could not be
+                if let Some(Err(_)) = loaded {
+                    *loaded = None;
+                }
+                if let Some(Err(_)) = synced {
+                    *synced = None;
+                }
+            }
+            Step::Vote {
+                choices,
+                proposals_version,
+            } => {
+                // the proposals are known once the vote plans are fetched, and
+                // replaced when they are fetched again
+                if *proposals_version != wallet.proposals_version {
+                    *choices = vec![None; wallet.proposals.len()];
+                    *proposals_version = wallet.proposals_version;
+                }
+                // the selected accounts and proposals may have changed since
+                // the choices were made, and the vote windows may have closed
+                for (proposal, choice) in choices.iter_mut().enumerate() {
+                    if !wallet.can_vote(proposal) || !wallet.is_open(proposal) {
+                        *choice = None;
+                    }
+                }
+                if choices.iter().any(Option::is_some) {
+                    wallet.make_choices(choices);
+                }
+            }
+            Step::Confirm { summaries } => {
+                *summaries = wallet
+                    .votes
+                    .iter()
+                    .map(|vote| fragment::decode(&vote.fragment))
+                    .collect();
+            }
+            Step::WaitConfirmation {
+                submissions,
+                progressed,
+                ..
+            } => {
+                // the step is only entered once, there is no going back from
+                // it
+                *submissions = wallet
+                    .votes
+                    .iter()
+                    .map(|vote| Submission {
+                        account: vote.account,
+                        proposal: vote.proposal,
+                        loaded: None,
+                        status: None,
+                        waited: Duration::default(),
+                        open_button: button::State::new(),
+                    })
+                    .collect();
+                skip_closed(submissions, wallet);
+                *progressed = Transfer::default();
+            }
+            _ => (),
+        }
+    }
+
     fn update(&mut self, msg: StepMessage, wallet: &mut Wallet) {
         match msg {
             StepMessage::ChangeKey(input) => {
+                if let Step::EnterKey { key, .. } = self {
+                    *key = Zeroizing::new(input);
+                }
+                self.check_secret();
+            }
+            StepMessage::ChangePassphrase(input) => {
+                if let Step::EnterKey { passphrase, .. } = self {
+                    *passphrase = Zeroizing::new(input);
+                }
+                self.check_secret();
+            }
+            StepMessage::Autocomplete(word) => {
+                if let Step::EnterKey { key, .. } = self {
+                    *key = Zeroizing::new(mnemonic::complete(key, word));
+                }
+                self.check_secret();
+            }
+            StepMessage::ToggleReveal => {
+                if let Step::EnterKey { reveal, .. } = self {
+                    *reveal = !*reveal;
+                }
+            }
+            StepMessage::PreviewAccount => {
+                if let Step::EnterKey {
+                    key,
+                    passphrase,
+                    error,
+                    preview,
+                    ..
+                } = self
+                {
+                    // the wallet is recovered again when moving on, the
+                    // keystore is saved then
+                    match keystore::Content::parse(key, passphrase)
+                        .and_then(|secret| wallet.recover(&secret))
+                    {
+                        Ok(()) => {
+                            *preview = wallet.id().map(str::to_owned);
+                            *error = None;
+                        }
+                        Err(message) => {
+                            *preview = None;
+                            *error = Some(message);
+                        }
+                    }
+                }
+            }
+            StepMessage::ChangeKeystorePassword(input) => {
+                if let Step::EnterKey { keystore, .. } = self {
+                    keystore.password = Zeroizing::new(input);
+                }
+            }
+            StepMessage::ToggleRemember(remember) => {
+                if let Step::EnterKey { keystore, .. } = self {
+                    keystore.remember = remember;
+                }
+            }
+            StepMessage::UnlockKeystore => {
                 if let Step::EnterKey {
                     retrieved,
                     key,
-                    state: _,
+                    passphrase,
+                    mnemonic,
+                    keystore,
                     error,
+                    ..
                 } = self
                 {
-                    *key = input;
-                    *error = wallet.recover(&key).err();
-                    *retrieved = wallet.wallet.is_some();
+                    let path = match &keystore.path {
+                        Some(path) => path,
+                        None => return,
+                    };
+
+                    match keystore::load(path, &keystore.password) {
+                        Ok(content) => {
+                            *error = wallet.recover(&content).err();
+                            *retrieved = error.is_none() && wallet.wallet.is_some();
+                            *mnemonic = mnemonic::Status::Empty;
+                            keystore.status = None;
+                            if *retrieved {
+                                key.zeroize();
+                                passphrase.zeroize();
+                                keystore.password.zeroize();
+                            }
+                        }
+                        Err(error) => keystore.status = Some(Err(error.to_string())),
+                    }
                 }
             }
             StepMessage::State { progress } => {
-                if let Step::LoadState { loaded, progressed } = self {
+                if let Step::LoadState {
+                    loaded,
+                    deselected,
+                    picked,
+                    progressed,
+                    ..
+                } = self
+                {
+                    // the progress is always about the first account not loaded yet
+                    let index = match loaded.iter().position(Option::is_none) {
+                        Some(index) => index,
+                        None => return,
+                    };
+                    let state = &mut loaded[index];
+
                     match progress {
-                        wallet_state::Progress::Started => *progressed = 0.0,
-                        wallet_state::Progress::Advanced(f) => *progressed = f,
-                        wallet_state::Progress::Finished { account_state } => {
-                            wallet.set_state(
-                                chain::Value(account_state.value),
-                                account_state.counter,
+                        wallet_state::Progress::Started => *progressed = Transfer::default(),
+                        wallet_state::Progress::Advanced(f) => *progressed = Transfer::Known(f),
+                        wallet_state::Progress::Received(bytes) => {
+                            *progressed = Transfer::Unknown(bytes)
+                        }
+                        wallet_state::Progress::Retrying { attempt, .. } => {
+                            *progressed = Transfer::Retrying(attempt)
+                        }
+                        wallet_state::Progress::Finished(account_state) => {
+                            *state = Some(
+                                wallet
+                                    .set_state(
+                                        index,
+                                        chain::Value(account_state.value),
+                                        account_state.counter,
+                                    )
+                                    .map(|()| account_state)
+                                    .map_err(|error| format!("Error: {}", error)),
                             );
-                            *loaded = Some(Ok(account_state));
                         }
-                        wallet_state::Progress::Errored { status_code } => {
-                            dbg!(status_code);
-                            *loaded = Some(Err("Account not found".to_owned()));
+                        wallet_state::Progress::Failed(error) => {
+                            *state = Some(Err(format!("Error: {}", error)));
                         }
-                        wallet_state::Progress::Failure { error } => {
-                            *loaded = Some(Err(format!("Error: {}", error)));
+                    }
+
+                    // an account without state cannot vote, another one is
+                    // picked if none is left. The account votes again if a
+                    // retry loads its state, instead of the picked one.
+                    match state {
+                        Some(Err(_)) if wallet.accounts[index].selected => {
+                            wallet.accounts[index].selected = false;
+                            deselected[index] = true;
+                        }
+                        Some(Ok(_)) if deselected[index] => {
+                            wallet.accounts[index].selected = true;
+                            deselected[index] = false;
+                            if let Some(picked) = picked.take() {
+                                wallet.accounts[picked].selected = false;
+                            }
+                        }
+                        _ => (),
+                    }
+                    if !wallet.accounts.iter().any(|account| account.selected) {
+                        if let Some(first) = loaded.iter().position(|l| matches!(l, Some(Ok(_)))) {
+                            wallet.accounts[first].selected = true;
+                            *picked = Some(first);
                         }
                     }
                 }
             }
             StepMessage::Transaction { progress } => {
                 if let Step::WaitConfirmation {
+                    submissions,
+                    progressed,
+                    ..
+                } = self
+                {
+                    // the progress is always about the first vote not sent yet
+                    let index = match submissions.iter().position(|s| s.loaded.is_none()) {
+                        Some(index) => index,
+                        None => return,
+                    };
+
+                    match progress {
+                        send_transaction::Progress::Started => *progressed = Transfer::default(),
+                        send_transaction::Progress::Advanced(f) => *progressed = Transfer::Known(f),
+                        send_transaction::Progress::Received(bytes) => {
+                            *progressed = Transfer::Unknown(bytes)
+                        }
+                        send_transaction::Progress::Retrying { attempt, .. } => {
+                            *progressed = Transfer::Retrying(attempt)
+                        }
+                        send_transaction::Progress::Finished(id) => {
+                            submissions[index].loaded = Some(Ok(id));
+                            skip_closed(submissions, wallet);
+                        }
+                        send_transaction::Progress::Failed(error) => {
+                            fail(submissions, index, format!("Error: {}", error));
+                            skip_closed(submissions, wallet);
+                        }
+                    }
+                }
+            }
+            StepMessage::Status { progress } => {
+                if let Step::WaitConfirmation { submissions, .. } = self {
+                    // the progress is always about the first vote sent and
+                    // not in a block yet
+                    let submission = match submissions.iter_mut().find(|s| s.is_pending()) {
+                        Some(submission) => submission,
+                        None => return,
+                    };
+
+                    match progress {
+                        fragment_status::Progress::Pending { elapsed } => {
+                            submission.waited = elapsed
+                        }
+                        fragment_status::Progress::Finished(status) => {
+                            submission.status = Some(Ok(status))
+                        }
+                        fragment_status::Progress::TimedOut(timeout) => {
+                            submission.status = Some(Err(format!(
+                                "The vote is still not in a block after {} seconds, \
+it may be later: check the transaction on the explorer",
+                                timeout.as_secs()
+                            )))
+                        }
+                        fragment_status::Progress::Failed(error) => {
+                            submission.status = Some(Err(format!(
+                                "Cannot check if the vote is in a block: {}",
+                                error
+                            )))
+                        }
+                    }
+                }
+            }
+            StepMessage::VotePlans { progress } => {
+                if let Step::VotePlans {
                     loaded, progressed, ..
                 } = self
                 {
+                    if loaded.is_some() {
+                        return;
+                    }
+
                     match progress {
-                        send_transaction::Progress::Started => *progressed = 0.0,
-                        send_transaction::Progress::Advanced(f) => *progressed = f,
-                        send_transaction::Progress::Finished { id } => {
-                            *loaded = Some(Ok(id));
+                        vote_plans::Progress::Started => *progressed = Transfer::default(),
+                        vote_plans::Progress::Advanced(f) => *progressed = Transfer::Known(f),
+                        vote_plans::Progress::Received(bytes) => {
+                            *progressed = Transfer::Unknown(bytes)
+                        }
+                        vote_plans::Progress::Retrying { attempt, .. } => {
+                            *progressed = Transfer::Retrying(attempt)
                         }
-                        send_transaction::Progress::Errored { status_code } => {
-                            dbg!(status_code);
-                            *loaded = Some(Err("Cannot send vote".to_owned()));
+                        vote_plans::Progress::Finished(statuses) => {
+                            *loaded = Some(Ok(wallet.set_vote_plans(statuses)));
                         }
-                        send_transaction::Progress::Failure { error } => {
+                        vote_plans::Progress::Failed(error) => {
                             *loaded = Some(Err(format!("Error: {}", error)));
                         }
                     }
                 }
             }
-            StepMessage::SelectVote(new_choice) => {
-                if let Step::Vote { choice, .. } = self {
-                    *choice = Some(new_choice);
-                    wallet.make_choice(new_choice);
+            StepMessage::Settings { progress } => {
+                if let Step::VotePlans { synced, .. } = self {
+                    if synced.is_some() {
+                        return;
+                    }
+
+                    match progress {
+                        node_settings::Progress::Finished(settings) => {
+                            *synced = Some(wallet.set_node_settings(&settings));
+                        }
+                        node_settings::Progress::Failed(error) => {
+                            *synced = Some(Err(node_settings::Error::Download(error)));
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            StepMessage::Block0 { loaded: block0 } => {
+                if let Step::Welcome { loaded, .. } = self {
+                    if loaded.is_none() {
+                        *loaded = Some(block0.and_then(|block0| wallet.set_block0(block0)));
+                    }
+                }
+            }
+            StepMessage::Retry => match self {
+                Step::Welcome {
+                    loaded, attempt, ..
+                } => {
+                    if let Some(Err(_)) = loaded {
+                        *loaded = None;
+                    }
+                    *attempt += 1;
+                }
+                Step::LoadState {
+                    loaded,
+                    progressed,
+                    attempt,
+                    ..
+                } => {
+                    for state in loaded.iter_mut() {
+                        if let Some(Err(_)) = state {
+                            *state = None;
+                        }
+                    }
+                    *progressed = Transfer::default();
+                    *attempt += 1;
+                }
+                Step::VotePlans {
+                    loaded,
+                    synced,
+                    progressed,
+                    attempt,
+                    ..
+                } => {
+                    if let Some(Err(_)) = loaded {
+                        *loaded = None;
+                    }
+                    if let Some(Err(_)) = synced {
+                        *synced = None;
+                    }
+                    *progressed = Transfer::default();
+                    *attempt += 1;
+                }
+                Step::WaitConfirmation {
+                    submissions,
+                    progressed,
+                    attempt,
+                    ..
+                } => {
+                    // sending a vote the node already received again is
+                    // harmless, it keeps the same fragment id
+                    for submission in submissions.iter_mut() {
+                        if let Some(Err(_)) = submission.loaded {
+                            submission.loaded = None;
+                        }
+                        if let Some(Err(_)) = submission.status {
+                            submission.status = None;
+                        }
+                    }
+                    skip_closed(submissions, wallet);
+                    *progressed = Transfer::default();
+                    *attempt += 1;
+                }
+                _ => (),
+            },
+            StepMessage::SelectAccount(account, selected) => {
+                if let Step::LoadState {
+                    deselected, picked, ..
+                } = self
+                {
+                    // at least one account votes
+                    let others = wallet
+                        .accounts
+                        .iter()
+                        .enumerate()
+                        .any(|(index, other)| index != account && other.selected);
+                    if selected || others {
+                        wallet.accounts[account].selected = selected;
+                        // the user's choice is kept over the automatic ones
+                        deselected
+                            .iter_mut()
+                            .for_each(|deselected| *deselected = false);
+                        *picked = None;
+                    }
+                }
+            }
+            StepMessage::SelectProposal(proposal, selected) => {
+                if let Step::VotePlans { .. } = self {
+                    wallet.proposals[proposal].selected = selected;
+                }
+            }
+            StepMessage::SelectVote(proposal, new_choice) => {
+                if let Step::Vote { choices, .. } = self {
+                    if wallet.is_open(proposal) {
+                        choices[proposal] = Some(new_choice);
+                        wallet.make_choices(choices);
+                    }
+                }
+            }
+            StepMessage::Tick => {
+                if let Step::Vote { choices, .. } = self {
+                    let mut closed = false;
+                    for (proposal, choice) in choices.iter_mut().enumerate() {
+                        if choice.is_some() && !wallet.is_open(proposal) {
+                            *choice = None;
+                            closed = true;
+                        }
+                    }
+                    if closed {
+                        wallet.make_choices(choices);
+                    }
                 }
             }
         };
     }
 
+    /// check the secret being typed, the wallet is only recovered once the
+    /// user moves on: the passphrase may still be typed after the mnemonics
+    fn check_secret(&mut self) {
+        if let Step::EnterKey {
+            retrieved,
+            key,
+            mnemonic,
+            error,
+            preview,
+            ..
+        } = self
+        {
+            *mnemonic = if secret_key::is_secret_key(key) {
+                mnemonic::Status::Empty
+            } else {
+                mnemonic::check(key)
+            };
+            *retrieved = false;
+            *error = None;
+            *preview = None;
+        }
+    }
+
+    /// called when the wizard moves forward from this step, which is left
+    /// only if it returns true
+    fn leave(&mut self, wallet: &mut Wallet) -> bool {
+        match self {
+            Step::EnterKey {
+                retrieved,
+                key,
+                passphrase,
+                mnemonic,
+                keystore,
+                error,
+                ..
+            } => {
+                // unlocking the keystore recovers the wallet already
+                if *retrieved {
+                    return true;
+                }
+
+                let secret = match keystore::Content::parse(key, passphrase) {
+                    Ok(secret) => secret,
+                    Err(message) => {
+                        *error = Some(message);
+                        return false;
+                    }
+                };
+                *error = wallet.recover(&secret).err();
+                if error.is_some() {
+                    return false;
+                }
+
+                if keystore.remember {
+                    let path = match &keystore.path {
+                        Some(path) => path,
+                        None => return false,
+                    };
+                    keystore.status = Some(
+                        keystore::save(path, &keystore.password, &secret)
+                            .map(|()| format!("Wallet saved to {}", path.display()))
+                            .map_err(|error| error.to_string()),
+                    );
+                    if let Some(Err(_)) = keystore.status {
+                        return false;
+                    }
+                    keystore.password.zeroize();
+                    keystore.saved = true;
+                    keystore.remember = false;
+                }
+
+                // the wallet holds the keys now, the secret is not needed
+                // anymore
+                key.zeroize();
+                passphrase.zeroize();
+                *mnemonic = mnemonic::Status::Empty;
+                *retrieved = true;
+                true
+            }
+            _ => true,
+        }
+    }
+
     fn title(&self) -> &str {
         match self {
-            Step::Welcome => "Welcome",
+            Step::Welcome { .. } => "Welcome",
             Step::EnterKey { .. } => "Register",
             Step::LoadState { .. } => "Registering",
+            Step::VotePlans { .. } => "Vote plans",
             Step::Vote { .. } => "Vote",
+            Step::Confirm { .. } => "Review",
             Step::WaitConfirmation { .. } => "Confirming",
             Step::End => "Thank you for your contribution",
         }
     }
 
-    fn can_continue(&self) -> bool {
+    fn can_continue(&self, wallet: &Wallet) -> bool {
         match self {
-            Step::Welcome => true,
-            Step::EnterKey { retrieved, .. } => *retrieved,
-            Step::LoadState {
-                loaded,
-                progressed: _,
-            } => loaded.as_ref().map(|r| r.is_ok()).unwrap_or(false),
-            Step::Vote { choice } => choice.is_some(),
-            Step::WaitConfirmation {
-                loaded,
-                progressed: _,
-                open_button: _,
-            } => loaded.is_some(),
+            Step::Welcome { loaded, .. } => matches!(loaded, Some(Ok(()))),
+            Step::EnterKey {
+                retrieved,
+                key,
+                mnemonic,
+                keystore,
+                ..
+            } => {
+                *retrieved
+                    || ((secret_key::is_secret_key(key) || mnemonic.is_complete())
+                        && !(keystore.remember && keystore.password.is_empty()))
+            }
+            Step::LoadState { loaded, .. } => {
+                loaded.iter().all(Option::is_some)
+                    && loaded.iter().any(|l| matches!(l, Some(Ok(_))))
+            }
+            // the votes are signed with the block0's settings when the node's
+            // are missing, but the node must run the blockchain of the block0
+            Step::VotePlans { loaded, synced, .. } => {
+                matches!(loaded, Some(Ok(_)))
+                    && synced.is_some()
+                    && !matches!(synced, Some(Err(node_settings::Error::Block0(_))))
+            }
+            // the accounts must pay the fees of their votes
+            Step::Vote { choices, .. } => {
+                choices.iter().any(Option::is_some) && wallet.unfunded(choices).is_empty()
+            }
+            // the vote windows may have closed while the votes were reviewed
+            Step::Confirm { summaries } => {
+                !summaries.is_empty()
+                    && summaries.iter().all(Result::is_ok)
+                    && wallet
+                        .votes
+                        .iter()
+                        .all(|vote| wallet.is_open(vote.proposal))
+            }
+            Step::WaitConfirmation { submissions, .. } => {
+                !submissions.is_empty()
+                    && submissions
+                        .iter()
+                        .all(|s| s.loaded.is_some() && !s.is_pending())
+            }
             Step::End => false,
         }
     }
 
-    fn view(&mut self) -> Element<Message> {
+    /// the choices are locked once the votes are being sent: changing them
+    /// would sign other votes with the spending counters already used
+    fn can_go_back(&self) -> bool {
+        !matches!(self, Step::WaitConfirmation { .. })
+    }
+
+    fn view(&'a mut self, wallet: &'a Wallet, endpoints: &Endpoints) -> Element<'a, Message> {
         match self {
-            Step::Welcome => Self::welcome(),
+            Step::Welcome {
+                loaded,
+                retry_button,
+                ..
+            } => Self::welcome(loaded, retry_button),
             Step::EnterKey {
-                key, state, error, ..
-            } => Self::staking_wallet(key, state, error),
-            Step::LoadState { loaded, progressed } => Self::view_get_state(*progressed, loaded),
-            Step::Vote { choice } => Self::make_choice(choice),
-            Step::WaitConfirmation {
+                key,
+                passphrase,
+                reveal,
+                retrieved,
+                state,
+                passphrase_state,
+                reveal_button,
+                mnemonic,
+                suggestion_buttons,
+                keystore,
+                error,
+                preview,
+                preview_button,
+            } => Self::staking_wallet(
+                key,
+                passphrase,
+                *reveal,
+                state,
+                passphrase_state,
+                reveal_button,
+                mnemonic,
+                suggestion_buttons,
+                error,
+                if *retrieved {
+                    wallet.id()
+                } else {
+                    preview.as_deref()
+                },
+                preview_button,
+            )
+            .push(Self::view_keystore(keystore)),
+            Step::LoadState {
                 loaded,
                 progressed,
-                open_button,
-            } => Self::view_send_vote(*progressed, loaded, open_button),
+                retry_button,
+                ..
+            } => Self::view_get_state(wallet, *progressed, loaded, retry_button),
+            Step::VotePlans {
+                loaded,
+                synced,
+                progressed,
+                retry_button,
+                ..
+            } => Self::view_vote_plans(wallet, *progressed, loaded, synced, retry_button),
+            Step::Vote { choices, .. } => Self::make_choice(wallet, choices),
+            Step::Confirm { summaries } => Self::view_confirm(wallet, summaries),
+            Step::WaitConfirmation {
+                submissions,
+                progressed,
+                retry_button,
+                ..
+            } => Self::view_send_vote(wallet, endpoints, *progressed, submissions, retry_button),
             Step::End => Self::end(),
         }
         .into()
@@ -457,7 +1815,26 @@ impl<'a> Step {
         Column::new().spacing(20).push(Text::new(title).size(50))
     }
 
-    fn welcome() -> Column<'a, Message> {
+    fn welcome(
+        loaded: &Option<Result<(), String>>,
+        retry_button: &'a mut button::State,
+    ) -> Column<'a, Message> {
+        let mut block0 = Column::new().spacing(20).align_items(Align::Center);
+        match loaded {
+            None => block0 = block0.push(Text::new("Loading the block0 of the network...")),
+            Some(Err(error)) => {
+                block0 = block0
+                    .push(Text::new("Cannot load the block0 of the network!"))
+                    .push(Text::new(error.to_owned()))
+                    .push(
+                        button(retry_button, "Retry")
+                            .on_press(Message::StepMessage(StepMessage::Retry))
+                            .style(style::Button::Secondary),
+                    );
+            }
+            Some(Ok(())) => (),
+        }
+
         Self::container("Welcome!")
             .push(Text::new(
                 "The Incentivised TestNet has been running for more than 6 months. \
@@ -470,139 +1847,694 @@ We thought we would give you an opportunity to vote to decide its fate.\
 the account style wallet and it is straightforward your wallet's mnemonics. \
 Or you have been using UTxO base wallet and you need to enter your stake private key.",
             ))
+            .push(block0)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn staking_wallet(
         key: &str,
+        passphrase: &str,
+        reveal: bool,
         state: &'a mut text_input::State,
-        error: &Option<chain::Error>,
+        passphrase_state: &'a mut text_input::State,
+        reveal_button: &'a mut button::State,
+        mnemonic: &mnemonic::Status,
+        suggestion_buttons: &'a mut [button::State],
+        error: &Option<String>,
+        account_id: Option<&str>,
+        preview_button: &'a mut button::State,
     ) -> Column<'a, Message> {
-        let key_input: Element<_> = TextInput::new(state, "Inputs...", key, StepMessage::ChangeKey)
+        let mut key_input = TextInput::new(state, "Inputs...", key, StepMessage::ChangeKey)
             .padding(10)
-            .size(30)
-            .into();
+            .size(30);
+        let mut passphrase_input = TextInput::new(
+            passphrase_state,
+            "Mnemonics passphrase (optional)",
+            passphrase,
+            StepMessage::ChangePassphrase,
+        )
+        .padding(10)
+        .size(20);
+        if !reveal {
+            key_input = key_input.password();
+            passphrase_input = passphrase_input.password();
+        }
+        let key_input: Element<_> = key_input.into();
+        let passphrase_input: Element<_> = passphrase_input.into();
+
+        let reveal_button = button(reveal_button, if reveal { "Hide" } else { "Show" })
+            .on_press(Message::StepMessage(StepMessage::ToggleReveal))
+            .style(style::Button::Secondary);
+
+        // the suggested words would show the masked mnemonics
+        let suggested = if reveal {
+            mnemonic::suggestions(key, suggestion_buttons.len())
+        } else {
+            Vec::new()
+        };
+        let suggestions = suggested
+            .into_iter()
+            .zip(suggestion_buttons.iter_mut())
+            .fold(Row::new().spacing(10), |row, (word, state)| {
+                row.push(
+                    button(state, word)
+                        .on_press(Message::StepMessage(StepMessage::Autocomplete(word)))
+                        .style(style::Button::Secondary),
+                )
+            });
 
         let error = if let Some(error) = error {
-            Text::new(error.to_string())
+            Text::new(error)
         } else {
             Text::new("")
         };
 
+        let mut preview_button =
+            button(preview_button, "Show the account").style(style::Button::Secondary);
+        if secret_key::is_secret_key(key) || mnemonic.is_complete() {
+            preview_button =
+                preview_button.on_press(Message::StepMessage(StepMessage::PreviewAccount));
+        }
+
         Self::container("Retrieve your stake key")
             .push(Text::new(
-                "Use your account mnemonics or your StakeKey private key",
+                "Use your account mnemonics or your StakeKey private key \
+(ed25519 or ed25519 extended, bech32 or hex encoded)",
+            ))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(key_input.map(Message::StepMessage))
+                    .push(reveal_button),
+            )
+            .push(suggestions)
+            .push(Text::new(mnemonic.to_string()))
+            .push(Text::new(
+                "If your mnemonics are protected by a passphrase enter it below, \
+leave it empty otherwise. It is not used with a private key.",
             ))
-            .push(key_input.map(Message::StepMessage))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(passphrase_input.map(Message::StepMessage))
+                    .push(preview_button),
+            )
             .push(error)
+            .push(Text::new(match account_id {
+                Some(id) => format!("Recovered account: {}", id),
+                None => String::new(),
+            }))
     }
 
-    fn make_choice(choice: &Option<Choice>) -> Column<'a, Message> {
-        let question: Element<_> = Column::new()
-            .padding(20)
-            .spacing(10)
-            .push(Text::new("Do you want to top up the reward pot of the ITN of 95M Ada?").size(24))
-            .push(Choice::all().iter().cloned().fold(
-                Column::new().padding(10).spacing(20),
-                |choices, language| {
-                    choices.push(Radio::new(
-                        language,
-                        language,
-                        *choice,
-                        StepMessage::SelectVote,
-                    ))
+    fn view_keystore(keystore: &'a mut KeystoreInput) -> Column<'a, Message> {
+        let mut content = Column::new().spacing(10);
+        if keystore.path.is_none() {
+            return content;
+        }
+
+        if keystore.saved {
+            content = content.push(Text::new(
+                "A wallet is saved on this computer: enter its password and unlock it \
+instead of entering your secret again.",
+            ));
+        }
+        content = content.push(Checkbox::new(
+            keystore.remember,
+            "Save the wallet on this computer, encrypted with a password",
+            |remember| Message::StepMessage(StepMessage::ToggleRemember(remember)),
+        ));
+
+        if !keystore.saved && !keystore.remember {
+            return content;
+        }
+
+        let password_input: Element<_> = TextInput::new(
+            &mut keystore.password_state,
+            "Keystore password",
+            &keystore.password,
+            StepMessage::ChangeKeystorePassword,
+        )
+        .password()
+        .padding(10)
+        .size(20)
+        .into();
+
+        let mut controls = Row::new().spacing(10);
+        if keystore.saved {
+            let mut unlock = button(&mut keystore.unlock_button, "Unlock saved wallet")
+                .style(style::Button::Secondary);
+            if !keystore.password.is_empty() {
+                unlock = unlock.on_press(Message::StepMessage(StepMessage::UnlockKeystore));
+            }
+            controls = controls.push(unlock);
+        }
+        if keystore.remember {
+            controls = controls.push(Text::new(
+                "The wallet is saved with this password when you continue",
+            ));
+        }
+
+        content = content
+            .push(password_input.map(Message::StepMessage))
+            .push(controls);
+
+        match &keystore.status {
+            Some(Ok(status)) => content.push(Text::new(status.to_owned())),
+            Some(Err(error)) => content.push(Text::new(format!("Keystore: {}", error))),
+            None => content,
+        }
+    }
+
+    fn make_choice(wallet: &Wallet, choices: &[Option<Choice>]) -> Column<'a, Message> {
+        let questions: Element<_> = wallet
+            .proposals
+            .iter()
+            .zip(choices.iter())
+            .enumerate()
+            .filter(|(_, (proposal, _))| proposal.selected)
+            .fold(
+                Column::new().spacing(10),
+                |questions, (index, (proposal, choice))| {
+                    let mut question = Column::new()
+                        .padding(20)
+                        .spacing(10)
+                        .push(Text::new(&proposal.title).size(24));
+
+                    let status = wallet.window_status(index);
+                    if let (Some(status), Some(window)) = (status, proposal.window) {
+                        question = question.push(Text::new(match status {
+                            WindowStatus::Upcoming(delay) => format!(
+                                "The vote opens in {}, at block date {}.",
+                                blockchain_time::format_duration(delay),
+                                window.start
+                            ),
+                            WindowStatus::Open(remaining) => format!(
+                                "The vote closes in {}, at block date {}.",
+                                blockchain_time::format_duration(remaining),
+                                window.end
+                            ),
+                            WindowStatus::Closed => {
+                                format!("The vote closed at block date {}.", window.end)
+                            }
+                        }));
+                    }
+
+                    let question = if !wallet.is_open(index) {
+                        question
+                    } else if wallet.can_vote(index) {
+                        question.push(proposal.choices().fold(
+                            Column::new().padding(10).spacing(20),
+                            |options, (option, label)| {
+                                options.push(Radio::new(option, label, *choice, move |choice| {
+                                    StepMessage::SelectVote(index, choice)
+                                }))
+                            },
+                        ))
+                    } else {
+                        question.push(Text::new(format!(
+                            "None of the selected accounts can vote on this proposal, \
+it needs a voting power of at least {}.",
+                            format_value(proposal.voting_power_threshold)
+                        )))
+                    };
+
+                    questions.push(question)
                 },
-            ))
+            )
             .into();
 
+        let votable = (0..wallet.proposals.len()).filter(|index| wallet.can_vote(*index));
+        let instructions = if votable.clone().any(|index| wallet.is_open(index)) {
+            "Select your choice on every proposal you want to vote on."
+        } else if !wallet.proposals.iter().any(|proposal| proposal.selected) {
+            "No proposal is selected, go back to select the proposals to vote on."
+        } else if votable.count() > 0 {
+            "None of the selected proposals accepts votes at the moment."
+        } else {
+            "Your accounts cannot vote: their voting power is below the minimum \
+required by the vote plans. Go back to select other accounts."
+        };
+
+        let mut fees = Column::new().spacing(5);
+        if let Some(fee) = wallet.vote_fee() {
+            fees = fees.push(Text::new(format!(
+                "Every vote costs a fee of {}.",
+                format_value(fee)
+            )));
+        }
+        if !wallet.settings_changes.is_empty() {
+            fees = fees.push(Text::new(format!(
+                "The node's settings are used, they differ from the block0's: {}.",
+                wallet.settings_changes.join(", ")
+            )));
+        }
+        for (account, needed) in wallet.unfunded(choices) {
+            let account = &wallet.accounts[account];
+            fees = fees.push(Text::new(format!(
+                "The account {} cannot pay the fees of its votes: {} are needed but it has {}.",
+                account.id,
+                format_value(needed),
+                format_value(account.voting_power().unwrap_or(0))
+            )));
+        }
+
+        let date = wallet
+            .clock
+            .and_then(|clock| clock.date(clock.now()))
+            .map(|date| format!("Current block date: {}", date))
+            .unwrap_or_default();
+
         Self::container("Cast your vote: The community needs you!")
-            .push(question.map(Message::StepMessage))
+            .push(Text::new(instructions))
+            .push(Text::new(date))
+            .push(fees)
+            .push(questions.map(Message::StepMessage))
+    }
+
+    fn view_confirm(
+        wallet: &Wallet,
+        summaries: &[Result<VoteSummary, String>],
+    ) -> Column<'a, Message> {
+        let content = wallet.votes.iter().zip(summaries.iter()).fold(
+            Column::new().spacing(20).padding(10),
+            |content, (vote, summary)| {
+                let proposal = &wallet.proposals[vote.proposal];
+                let details = Column::new()
+                    .spacing(5)
+                    .push(Text::new(&proposal.title).size(24));
+
+                let details = match summary {
+                    Ok(summary) => {
+                        let label = |choice: u8| {
+                            proposal
+                                .choices()
+                                .find(|(c, _)| c.0 == choice)
+                                .map(|(_, label)| format!("{} ({})", choice, label))
+                                .unwrap_or_else(|| format!("{} (unknown option)", choice))
+                        };
+                        // the private choice cannot be read back from the
+                        // fragment, it is encrypted
+                        let choice = match summary.choice {
+                            Some(choice) => label(choice),
+                            None => format!("{}, encrypted", label(vote.choice.0)),
+                        };
+                        let account = summary.account.as_deref().unwrap_or("none");
+
+                        let mut details = details
+                            .push(Text::new(format!("Vote plan: {}", summary.vote_plan)))
+                            .push(Text::new(format!(
+                                "Proposal index: {}",
+                                summary.proposal_index
+                            )))
+                            .push(Text::new(format!("Choice: {}", choice)))
+                            .push(Text::new(format!("Fee: {}", format_value(summary.fee))))
+                            .push(Text::new(format!("Input account: {}", account)))
+                            // the counter is only signed, it cannot be read
+                            // back from the fragment
+                            .push(Text::new(format!(
+                                "Expected spending counter: {}",
+                                vote.counter
+                            )))
+                            .push(Text::new(format!("Fragment id: {}", summary.fragment_id)));
+
+                        if summary.account.as_deref() != Some(&wallet.accounts[vote.account].id) {
+                            details = details.push(Text::new(
+                                "Warning: the vote is not paid by the selected account!",
+                            ));
+                        }
+                        if let Some(fee) = wallet.vote_fee().filter(|fee| *fee != summary.fee) {
+                            details = details.push(Text::new(format!(
+                                "Warning: the fee differs from the {} of the blockchain settings!",
+                                format_value(fee)
+                            )));
+                        }
+
+                        details
+                    }
+                    Err(error) => details
+                        .push(Text::new("Cannot decode the vote!"))
+                        .push(Text::new(error.to_owned())),
+                };
+                let details = if wallet.is_open(vote.proposal) {
+                    details
+                } else {
+                    details.push(Text::new(
+                        "The vote plan no longer accepts votes, go back to change the choices.",
+                    ))
+                };
+
+                content.push(details)
+            },
+        );
+
+        let total: u64 = summaries
+            .iter()
+            .filter_map(|summary| summary.as_ref().ok())
+            .map(|summary| summary.fee)
+            .sum();
+        let content = wallet.skipped.iter().fold(content, |content, skipped| {
+            let proposal = &wallet.proposals[skipped.proposal];
+            let title = if wallet.accounts.len() > 1 {
+                let account = &wallet.accounts[skipped.account];
+                format!("{} ({})", proposal.title, account.id)
+            } else {
+                proposal.title.clone()
+            };
+
+            content.push(
+                Column::new()
+                    .spacing(5)
+                    .push(Text::new(title).size(24))
+                    .push(Text::new("Not signed, the vote will not be sent!"))
+                    .push(Text::new(skipped.reason.to_owned())),
+            )
+        });
+        let content = if wallet.votes.is_empty() {
+            content.push(Text::new(
+                "No vote could be signed, go back and check your choices.",
+            ))
+        } else {
+            content.push(Text::new(format!("Total fees: {}", format_value(total))))
+        };
+
+        Self::container("Review your votes")
+            .push(Text::new(
+                "Check the content of the signed votes before sending them to the blockchain. \
+Once they are sent the choices cannot be changed.",
+            ))
+            .push(content)
     }
 
     fn view_get_state(
-        current_progress: f32,
-        data: &Option<Result<AccountState, String>>,
+        wallet: &Wallet,
+        current_progress: Transfer,
+        loaded: &[Option<Result<AccountState, String>>],
+        retry_button: &'a mut button::State,
     ) -> Column<'a, Message> {
-        let progress_bar = ProgressBar::new(0.0..=100.0, current_progress);
+        let loading = loaded.iter().position(Option::is_none);
+        let mut content = Column::new()
+            .spacing(10)
+            .padding(10)
+            .align_items(Align::Center);
 
-        let control: Element<_> = if let Some(result) = data {
-            match result {
-                Ok(account_state) => Column::new()
+        if loading.is_some() {
+            content = content.push(current_progress.bar());
+        }
+
+        let accounts = &wallet.accounts;
+        for (index, (account, state)) in accounts.iter().zip(loaded.iter()).enumerate() {
+            let control: Element<_> = match state {
+                Some(Ok(account_state)) => {
+                    let selected = wallet
+                        .proposals
+                        .iter()
+                        .filter(|proposal| proposal.selected)
+                        .count();
+                    let excluded = wallet
+                        .proposals
+                        .iter()
+                        .filter(|proposal| proposal.selected && !proposal.can_vote(account))
+                        .count();
+                    let threshold = if excluded > 0 && excluded == selected {
+                        "The voting power is below the minimum of the vote plans, \
+the account cannot vote."
+                            .to_owned()
+                    } else if excluded > 0 {
+                        format!(
+                            "The voting power is below the minimum of {} of the selected proposals.",
+                            excluded
+                        )
+                    } else {
+                        String::new()
+                    };
+
+                    Column::new()
+                        .spacing(10)
+                        .push(Checkbox::new(
+                            account.selected,
+                            &account.id,
+                            move |selected| StepMessage::SelectAccount(index, selected),
+                        ))
+                        .push(Text::new(format!(
+                            "Voting power: {}",
+                            format_value(account_state.value)
+                        )))
+                        .push(Text::new(format!(
+                            "Spending counter: {}",
+                            account_state.counter
+                        )))
+                        .push(Text::new(format!(
+                            "Delegation: {}",
+                            account_state.delegation
+                        )))
+                        .push(Text::new(match &account_state.last_rewards {
+                            Some(rewards) => format!(
+                                "Last rewards: {} in epoch {}",
+                                format_value(rewards.reward),
+                                rewards.epoch
+                            ),
+                            None => "Last rewards: none".to_owned(),
+                        }))
+                        .push(Text::new(threshold))
+                        .into()
+                }
+                Some(Err(error)) => Column::new()
                     .spacing(10)
-                    .align_items(Align::Center)
-                    .push(Text::new("Wallet synced finished!"))
-                    .push(Text::new(format!(
-                        "retrieved value {}",
-                        account_state.value
-                    )))
-                    .push(Text::new(format!(
-                        "retrieved counter {}",
-                        account_state.counter
-                    )))
+                    .push(Text::new(&account.id))
+                    .push(Text::new("Cannot sync the account!"))
+                    .push(Text::new(error.to_owned()))
                     .into(),
-                Err(error) => Column::new()
+                None if loading == Some(index) => Column::new()
                     .spacing(10)
-                    .align_items(Align::Center)
-                    .push(Text::new("Cannot sync the wallet!"))
-                    .push(Text::new(error.to_owned()))
+                    .push(Text::new(&account.id))
+                    .push(Text::new(format!("Downloading... {}", current_progress)))
                     .into(),
-            }
+                None => Column::new()
+                    .spacing(10)
+                    .push(Text::new(&account.id))
+                    .push(Text::new("Waiting for the previous accounts..."))
+                    .into(),
+            };
+
+            content = content.push(control.map(Message::StepMessage));
+        }
+
+        let title = if accounts.len() > 1 {
+            "Select the accounts to vote with"
         } else {
-            Text::new(format!("Downloading... {:.2}%", current_progress)).into()
+            "Retrieving wallet data"
         };
-        let content = Column::new()
-            .spacing(10)
+        if loading.is_none() && loaded.iter().any(|l| matches!(l, Some(Err(_)))) {
+            content = content.push(
+                button(retry_button, "Retry")
+                    .on_press(Message::StepMessage(StepMessage::Retry))
+                    .style(style::Button::Secondary),
+            );
+        }
+
+        Self::container(title).push(content)
+    }
+
+    fn view_vote_plans(
+        wallet: &Wallet,
+        current_progress: Transfer,
+        loaded: &Option<Result<Vec<ActivePlan>, String>>,
+        synced: &Option<Result<(), node_settings::Error>>,
+        retry_button: &'a mut button::State,
+    ) -> Column<'a, Message> {
+        let mut content = Column::new()
+            .spacing(20)
             .padding(10)
-            .align_items(Align::Center)
-            .push(progress_bar)
-            .push(control);
+            .align_items(Align::Center);
+
+        if let Some(Err(error)) = synced {
+            let warning = match error {
+                node_settings::Error::Block0(_) => {
+                    "The node does not run the blockchain of the block0, the votes cannot be \
+sent to it!"
+                }
+                _ if wallet.use_live_settings && wallet.live_settings.is_none() => {
+                    "Cannot get the settings of the node, the votes are signed with the \
+block0's fees and the vote windows are checked with the computer's clock!"
+                }
+                _ => {
+                    "Cannot get the time of the node, the vote windows are checked with the \
+computer's clock!"
+                }
+            };
+            content = content
+                .push(Text::new(warning))
+                .push(Text::new(error.to_string()));
+        }
+        let failed = matches!(loaded, Some(Err(_))) || matches!(synced, Some(Err(_)));
+
+        match loaded {
+            None => {
+                content = content.push(current_progress.bar()).push(Text::new(format!(
+                    "Downloading the active vote plans... {}",
+                    current_progress
+                )));
+            }
+            Some(Err(error)) => {
+                content = content
+                    .push(Text::new("Cannot fetch the vote plans!"))
+                    .push(Text::new(error.to_owned()));
+            }
+            Some(Ok(plans)) if plans.is_empty() => {
+                content = content.push(Text::new("No vote plan is active on the node."));
+            }
+            Some(Ok(plans)) => {
+                for plan in plans {
+                    let status = &plan.status;
+                    let payload = match status.payload {
+                        PayloadType::Public => "Public vote",
+                        PayloadType::Private => "Private vote",
+                    };
+
+                    let mut control = Column::new()
+                        .spacing(10)
+                        .push(Text::new(format!("Vote plan {}", status.id)).size(20))
+                        .push(Text::new(format!(
+                            "Voting from {} to {}, tallied until {} (epoch.slot)",
+                            status.vote_start, status.vote_end, status.committee_end
+                        )))
+                        .push(Text::new(payload));
+
+                    if let Some(error) = &plan.error {
+                        control = control
+                            .push(Text::new("The proposals of this plan cannot be voted on!"))
+                            .push(Text::new(error.to_owned()));
+                    }
 
-        Self::container("Retrieving wallet data").push(content)
+                    for (index, proposal) in wallet.proposals.iter().enumerate() {
+                        if proposal.vote_plan != status.id {
+                            continue;
+                        }
+                        control = control.push(Checkbox::new(
+                            proposal.selected,
+                            &proposal.title,
+                            move |selected| StepMessage::SelectProposal(index, selected),
+                        ));
+                    }
+
+                    let control: Element<_> = control.into();
+                    content = content.push(control.map(Message::StepMessage));
+                }
+            }
+        }
+
+        if failed {
+            content = content.push(
+                button(retry_button, "Retry")
+                    .on_press(Message::StepMessage(StepMessage::Retry))
+                    .style(style::Button::Secondary),
+            );
+        }
+
+        Self::container("Select the proposals to vote on").push(content)
     }
 
     fn view_send_vote(
-        current_progress: f32,
-        data: &Option<Result<String, String>>,
-        open_button: &'a mut button::State,
+        wallet: &Wallet,
+        endpoints: &Endpoints,
+        current_progress: Transfer,
+        submissions: &'a mut [Submission],
+        retry_button: &'a mut button::State,
     ) -> Column<'a, Message> {
-        let progress_bar = ProgressBar::new(0.0..=100.0, current_progress);
+        let sending = submissions.iter().position(|s| s.loaded.is_none());
+        let failed = submissions
+            .iter()
+            .any(|s| matches!(s.loaded, Some(Err(_))) || matches!(s.status, Some(Err(_))));
+        let mut content = Column::new()
+            .spacing(10)
+            .padding(10)
+            .align_items(Align::Center);
 
-        let control: Element<_> = if let Some(result) = data {
-            match result {
-                Ok(state) => {
-                    Column::new()
-                    .spacing(10)
-                    .align_items(Align::Center)
-                    .push(Text::new("Vote sent successfully!"))
-                    .push(Text::new(format!(
-                        "The transaction id '{}' can be used to confirm the vote transaction ont the explorer",
-                        state
-                    )))
-                    .push(
-                        button(open_button, "Open transaction in explorer")
-                            .on_press(Message::OpenLink(format!("https://itnexplorer.cardano.org/en/transaction/{}/", state)))
-                            .style(style::Button::Secondary)
-                    )
-                    .into()
+        if sending.is_some() {
+            content = content.push(current_progress.bar());
+        }
+
+        for (index, submission) in submissions.iter_mut().enumerate() {
+            let proposal = &wallet.proposals[submission.proposal];
+            let title = if wallet.accounts.len() > 1 {
+                let account = &wallet.accounts[submission.account];
+                Text::new(format!("{} ({})", proposal.title, account.id)).size(24)
+            } else {
+                Text::new(&proposal.title).size(24)
+            };
+
+            let control: Element<_> = match &submission.loaded {
+                Some(Ok(state)) => {
+                    let status = match &submission.status {
+                        Some(Ok(fragment_status::Status::InABlock { date, block })) => {
+                            format!("The vote is in the block {} of date {}.", block, date)
+                        }
+                        Some(Ok(fragment_status::Status::Rejected { reason })) => {
+                            format!("The vote was rejected: {}", reason)
+                        }
+                        Some(Err(error)) => error.to_owned(),
+                        None => format!(
+                            "Waiting for the vote to be in a block... ({} seconds)",
+                            submission.waited.as_secs()
+                        ),
+                    };
+
+                    let mut column = Column::new()
+                        .spacing(10)
+                        .align_items(Align::Center)
+                        .push(title)
+                        .push(Text::new("Vote sent successfully!"))
+                        .push(Text::new(format!(
+                            "The transaction id '{}' can be used to confirm the vote transaction ont the explorer",
+                            state
+                        )))
+                        .push(Text::new(status));
+
+                    if let Some(link) = endpoints.explorer_link(state) {
+                        column = column.push(
+                            button(&mut submission.open_button, "Open transaction in explorer")
+                                .on_press(Message::OpenLink(link))
+                                .style(style::Button::Secondary),
+                        );
+                    }
+
+                    column.into()
                 }
-                Err(error) => Column::new()
+                Some(Err(error)) => Column::new()
                     .spacing(10)
                     .align_items(Align::Center)
+                    .push(title)
                     .push(Text::new("Cannot send the transaction!"))
                     .push(Text::new(error.to_owned()))
                     .into(),
-            }
-        } else {
-            Text::new(format!("Sending vote... {:.2}%", current_progress)).into()
-        };
-        let content: Element<_> = Column::new()
-            .spacing(10)
-            .padding(10)
-            .align_items(Align::Center)
-            .push(progress_bar)
-            .push(control)
-            .into();
+                None if sending == Some(index) => Column::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(title)
+                    .push(Text::new(format!("Sending vote... {}", current_progress)))
+                    .into(),
+                None => Column::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(title)
+                    .push(Text::new("Waiting for the previous votes to be sent..."))
+                    .into(),
+            };
+
+            content = content.push(control);
+        }
+
+        if sending.is_none() && failed {
+            content = content.push(
+                button(retry_button, "Retry")
+                    .on_press(Message::StepMessage(StepMessage::Retry))
+                    .style(style::Button::Secondary),
+            );
+        }
 
-        Self::container("Sending vote to the blockchain").push(content)
+        Self::container("Sending votes to the blockchain").push(content)
     }
 
     fn end() -> Column<'a, Message> {
@@ -618,6 +2550,30 @@ Or you have been using UTxO base wallet and you need to enter your stake private
     }
 }
 
+/// the fee of a vote, in lovelace
+fn vote_fee(fees: &LinearFee) -> u64 {
+    let certificate = fees
+        .per_vote_certificate_fees
+        .certificate_vote_cast
+        .map(NonZeroU64::get)
+        .unwrap_or(fees.certificate);
+
+    // the vote has a single input, the voting account, and no output
+    fees.constant + fees.coefficient + certificate
+}
+
+/// a value in ADA followed by its exact amount in lovelace
+pub fn format_value(lovelace: u64) -> String {
+    format!(
+        "{}.{:06} ADA ({} lovelace)",
+        lovelace / LOVELACE_PER_ADA,
+        lovelace % LOVELACE_PER_ADA,
+        lovelace
+    )
+}
+
+const LOVELACE_PER_ADA: u64 = 1_000_000;
+
 fn button<'a, Message>(state: &'a mut button::State, label: &str) -> Button<'a, Message> {
     Button::new(
         state,
@@ -627,36 +2583,56 @@ fn button<'a, Message>(state: &'a mut button::State, label: &str) -> Button<'a,
     .min_width(100)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Choice {
-    Blank,
-    Yes,
-    No,
+/// how much of the node's response has been received
+#[derive(Debug, Clone, Copy)]
+enum Transfer {
+    /// percentage of the response
+    Known(f32),
+    /// bytes received, the node did not tell the size of the response
+    Unknown(u64),
+    /// the request failed and is sent again, this is the attempt number
+    Retrying(u32),
 }
 
-impl Choice {
-    fn all() -> [Choice; 3] {
-        [Choice::Blank, Choice::Yes, Choice::No]
+impl Transfer {
+    /// a progress bar, full while the size of the response is unknown
+    fn bar(self) -> ProgressBar {
+        match self {
+            Transfer::Known(percentage) => ProgressBar::new(0.0..=100.0, percentage),
+            Transfer::Unknown(_) => ProgressBar::new(0.0..=100.0, 100.0),
+            Transfer::Retrying(_) => ProgressBar::new(0.0..=100.0, 0.0),
+        }
     }
 }
 
-impl From<Choice> for chain::Choice {
-    fn from(choice: Choice) -> Self {
-        chain::Choice::new(match choice {
-            Choice::Blank => 0,
-            Choice::Yes => 1,
-            Choice::No => 2,
-        })
+impl Default for Transfer {
+    fn default() -> Self {
+        Transfer::Known(0.0)
     }
 }
 
-impl From<Choice> for String {
-    fn from(choice: Choice) -> String {
-        String::from(match choice {
-            Choice::Blank => "Blank",
-            Choice::Yes => "Yes",
-            Choice::No => "No",
-        })
+impl std::fmt::Display for Transfer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Transfer::Known(percentage) => write!(f, "{:.2}%", percentage),
+            Transfer::Unknown(bytes) => write!(f, "{} bytes", bytes),
+            Transfer::Retrying(attempt) => write!(
+                f,
+                "retrying after an error ({}/{})",
+                attempt,
+                node_client::MAX_RETRIES
+            ),
+        }
+    }
+}
+
+/// one of the options of a proposal, identified by its index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Choice(u8);
+
+impl From<Choice> for chain::Choice {
+    fn from(choice: Choice) -> Self {
+        chain::Choice::new(choice.0)
     }
 }
 
@@ -697,3 +2673,74 @@ mod style {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VOTE_PLAN: &str = "d5bd73ca1b2cb59c44e9ca2e4aa3e4bc1a1aba2862fce19a9516e5041abfe92f";
+
+    /// a wallet voting on proposals of the given thresholds with selected
+    /// accounts of the given values
+    fn wallet(thresholds: &[u64], values: &[u64]) -> Wallet {
+        let vote_plan = config::VotePlan {
+            id: VOTE_PLAN.to_owned(),
+            payload_type: PayloadType::Public,
+            voting_power_threshold: 0,
+            proposals: (0..thresholds.len() as u8)
+                .map(|index| config::ProposalConfig {
+                    index,
+                    title: format!("Proposal {}", index),
+                    options: 2,
+                    labels: Vec::new(),
+                })
+                .collect(),
+        };
+        let mut proposals = vote_plan.proposals().unwrap();
+        for (proposal, threshold) in proposals.iter_mut().zip(thresholds) {
+            proposal.voting_power_threshold = *threshold;
+        }
+
+        let mut wallet = Wallet::new(proposals, Vec::new(), Vec::new());
+        wallet.accounts = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| Account {
+                id: format!("account {}", index),
+                state: Some((chain::Value(*value), 0)),
+                selected: true,
+            })
+            .collect();
+        wallet
+    }
+
+    #[test]
+    fn fee_of_a_vote() {
+        let mut fees = LinearFee::new(200_000, 100_000, 50_000);
+        assert_eq!(vote_fee(&fees), 350_000);
+
+        // the fee of the vote certificates replaces the one of all the
+        // certificates
+        fees.per_vote_certificate_fees.certificate_vote_cast = NonZeroU64::new(10_000);
+        assert_eq!(vote_fee(&fees), 310_000);
+    }
+
+    #[test]
+    fn accounts_paying_the_fees() {
+        let mut wallet = wallet(&[0, 120], &[50, 150, 1_000]);
+        let both = [Some(Choice(0)), Some(Choice(1))];
+        let first = [Some(Choice(0)), None];
+
+        // the first account is below the threshold of the second proposal, it
+        // only pays for one vote
+        assert_eq!(wallet.unfunded_with(&both, 100), vec![(0, 100), (1, 200)]);
+        assert_eq!(wallet.unfunded_with(&first, 100), vec![(0, 100)]);
+        assert!(wallet.unfunded_with(&[None, None], 100).is_empty());
+
+        wallet.accounts[0].selected = false;
+        assert!(wallet.unfunded_with(&first, 100).is_empty());
+
+        // the fees are only known once the wallet is recovered
+        assert!(wallet.unfunded(&both).is_empty());
+    }
+}
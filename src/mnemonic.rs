@@ -0,0 +1,239 @@
+use bip39::dictionary::{Language as _, ENGLISH};
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// the number of words a BIP39 phrase can have
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// what is wrong, or right, with the mnemonics typed so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Empty,
+    /// a word is not in the BIP39 English list, with its position (from 1).
+    ///
+    /// The word itself is not kept, the status is shown while the input is
+    /// masked.
+    UnknownWord {
+        position: usize,
+    },
+    /// every word is valid but more are expected
+    Incomplete {
+        words: usize,
+    },
+    TooManyWords {
+        words: usize,
+    },
+    /// the phrase has a valid length but its checksum does not match, more
+    /// words may still be expected
+    InvalidChecksum {
+        words: usize,
+    },
+    Complete {
+        words: usize,
+    },
+}
+
+/// check the mnemonics word by word, the last word being still typed unless it
+/// is followed by a space
+pub fn check(phrase: &str) -> Status {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    let typing = !phrase.ends_with(char::is_whitespace);
+
+    if words.is_empty() {
+        return Status::Empty;
+    }
+
+    for (index, word) in words.iter().enumerate() {
+        let is_last = index + 1 == words.len();
+        let known = ENGLISH.lookup_mnemonic(word).is_ok();
+        let partial = is_last && typing && !suggestions(phrase, 1).is_empty();
+
+        if !known && !partial {
+            return Status::UnknownWord {
+                position: index + 1,
+            };
+        }
+        if !known {
+            return Status::Incomplete { words: index };
+        }
+    }
+
+    let count = words.len();
+    if count > WORD_COUNTS[WORD_COUNTS.len() - 1] {
+        Status::TooManyWords { words: count }
+    } else if !WORD_COUNTS.contains(&count) {
+        Status::Incomplete { words: count }
+    } else {
+        let phrase = Zeroizing::new(words.join(" "));
+        match bip39::Mnemonics::from_string(&ENGLISH, &phrase)
+            .and_then(|mnemonics| bip39::Entropy::from_mnemonics(&mnemonics))
+        {
+            Ok(_) => Status::Complete { words: count },
+            Err(_) => Status::InvalidChecksum { words: count },
+        }
+    }
+}
+
+/// words of the BIP39 English list starting like the word being typed
+pub fn suggestions(phrase: &str, max: usize) -> Vec<&'static str> {
+    let prefix = match typed_word(phrase) {
+        Some(prefix) => prefix,
+        None => return Vec::new(),
+    };
+
+    ENGLISH
+        .words
+        .iter()
+        .filter(|word| word.starts_with(prefix) && **word != prefix)
+        .take(max)
+        .cloned()
+        .collect()
+}
+
+/// replace the word being typed with the given word
+pub fn complete(phrase: &str, word: &str) -> String {
+    let start = match typed_word(phrase) {
+        Some(prefix) => phrase.len() - prefix.len(),
+        None => phrase.len(),
+    };
+
+    format!("{}{} ", &phrase[..start], word)
+}
+
+/// the entropy the phrase encodes, its checksum being verified
+pub fn entropy(phrase: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    let mnemonics =
+        bip39::Mnemonics::from_string(&ENGLISH, phrase).map_err(|error| error.to_string())?;
+    let entropy = bip39::Entropy::from_mnemonics(&mnemonics).map_err(|error| error.to_string())?;
+
+    Ok(Zeroizing::new(entropy.to_vec()))
+}
+
+/// the phrase encoding the entropy
+pub fn from_entropy(entropy: &[u8]) -> Result<Zeroizing<String>, String> {
+    let entropy = bip39::Entropy::from_slice(entropy).map_err(|error| error.to_string())?;
+
+    Ok(Zeroizing::new(
+        entropy.to_mnemonics().to_string(&ENGLISH).to_string(),
+    ))
+}
+
+fn typed_word(phrase: &str) -> Option<&str> {
+    if phrase.ends_with(char::is_whitespace) {
+        None
+    } else {
+        phrase.split_whitespace().last()
+    }
+}
+
+impl Status {
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Status::Complete { .. })
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Empty => write!(f, ""),
+            Status::UnknownWord { position } => {
+                write!(f, "word {} is not a valid mnemonic word", position)
+            }
+            Status::Incomplete { words } => write!(
+                f,
+                "{} words, a phrase has 12, 15, 18, 21 or 24 words",
+                words
+            ),
+            Status::TooManyWords { words } => {
+                write!(f, "{} words, a phrase has at most 24 words", words)
+            }
+            Status::InvalidChecksum { words } => write!(
+                f,
+                "{} words, but the checksum does not match: check the words",
+                words
+            ),
+            Status::Complete { words } => write!(f, "{} words, the phrase is valid", words),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn check_phrases() {
+        assert_eq!(check(""), Status::Empty);
+        assert_eq!(check("   "), Status::Empty);
+        assert_eq!(check("aban"), Status::Incomplete { words: 0 });
+        assert_eq!(check("abandon"), Status::Incomplete { words: 1 });
+        assert_eq!(check("abandon ab"), Status::Incomplete { words: 1 });
+        assert_eq!(check(VALID), Status::Complete { words: 12 });
+        assert_eq!(
+            check(&format!("{} ", VALID)),
+            Status::Complete { words: 12 }
+        );
+        assert_eq!(
+            check(&format!("{} abandon", VALID)),
+            Status::Incomplete { words: 13 }
+        );
+        assert_eq!(
+            check(&["abandon"; 12].join(" ")),
+            Status::InvalidChecksum { words: 12 }
+        );
+        assert_eq!(
+            check(&["abandon"; 25].join(" ")),
+            Status::TooManyWords { words: 25 }
+        );
+    }
+
+    #[test]
+    fn reject_unknown_words() {
+        assert_eq!(check("zzz"), Status::UnknownWord { position: 1 });
+        assert_eq!(check("zzz abandon"), Status::UnknownWord { position: 1 });
+        assert_eq!(check("abandon zzz"), Status::UnknownWord { position: 2 });
+        // a word followed by a space is no longer being typed
+        assert_eq!(check("abandon aban "), Status::UnknownWord { position: 2 });
+        assert_eq!(check("Abandon"), Status::UnknownWord { position: 1 });
+    }
+
+    #[test]
+    fn suggest_words() {
+        assert_eq!(suggestions("abandon abo", 6), vec!["about", "above"]);
+        assert_eq!(suggestions("abandon abo", 1), vec!["about"]);
+        assert_eq!(suggestions("a", 3).len(), 3);
+        assert!(suggestions("", 6).is_empty());
+        assert!(suggestions("abandon ", 6).is_empty());
+        assert!(suggestions("abandon", 6).is_empty());
+        assert!(suggestions("zzz", 6).is_empty());
+    }
+
+    #[test]
+    fn entropy_roundtrip() {
+        let entropy = entropy(VALID).unwrap();
+        assert_eq!(*entropy, vec![0; 16]);
+        assert_eq!(*from_entropy(&entropy).unwrap(), VALID);
+
+        let entropy = [0xa5; 32];
+        let phrase = from_entropy(&entropy).unwrap();
+        assert_eq!(check(&phrase), Status::Complete { words: 24 });
+        assert_eq!(*self::entropy(&phrase).unwrap(), entropy.to_vec());
+    }
+
+    #[test]
+    fn reject_invalid_entropy() {
+        assert!(entropy(&["abandon"; 12].join(" ")).is_err());
+        assert!(from_entropy(&[0; 15]).is_err());
+        assert!(from_entropy(&[]).is_err());
+    }
+
+    #[test]
+    fn complete_words() {
+        assert_eq!(complete("", "abandon"), "abandon ");
+        assert_eq!(complete("aban", "abandon"), "abandon ");
+        assert_eq!(complete("abandon abo", "about"), "abandon about ");
+        assert_eq!(complete("abandon ", "about"), "abandon about ");
+    }
+}
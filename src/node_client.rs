@@ -0,0 +1,504 @@
+use iced_futures::futures::{self, stream::BoxStream, StreamExt as _};
+use once_cell::sync::Lazy;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use std::{fmt, marker::PhantomData, time::Duration};
+
+/// how long to wait for the connection to the node
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// how long to wait for the whole response of the node
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// how many times a request is sent again after a transient error
+pub const MAX_RETRIES: u32 = 3;
+/// delay before the first retry, doubled on every following one
+const BACKOFF: Duration = Duration::from_millis(500);
+
+/// the client shared by every request to the node.
+///
+/// The connections are not kept alive: the block0 is downloaded from another
+/// runtime than the app's one, the connections would not outlive it.
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(TIMEOUT)
+        .pool_max_idle_per_host(0)
+        .build()
+        .expect("cannot initialize the HTTP client")
+});
+
+/// a request to the node REST API
+#[derive(Debug, Clone, Hash)]
+pub struct Request {
+    method: Method,
+    url: String,
+    body: Option<Box<[u8]>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// the node cannot be reached
+    Connection {
+        url: String,
+        message: String,
+    },
+    Timeout {
+        url: String,
+    },
+    /// the node answered with an error status
+    Status {
+        url: String,
+        status_code: StatusCode,
+    },
+    /// the connection broke while receiving the response
+    Body {
+        url: String,
+        message: String,
+    },
+    /// the response is not what was expected
+    Invalid {
+        url: String,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum Progress<T> {
+    Started,
+    /// percentage of the response received
+    Advanced(f32),
+    /// bytes received so far, when the size of the response is unknown
+    Received(u64),
+    /// the request failed with a transient error and is sent again
+    Retrying {
+        attempt: u32,
+        error: Error,
+    },
+    Finished(T),
+    Failed(Error),
+}
+
+/// a query of a JSON document of the node REST API
+pub struct Query<T> {
+    request: Request,
+    /// a new attempt starts the query again
+    attempt: u32,
+    document: PhantomData<fn() -> T>,
+}
+
+enum State {
+    Ready {
+        request: Request,
+        attempt: u32,
+    },
+    /// the request is sent again once the backoff delay of the attempt is
+    /// over
+    Backoff {
+        request: Request,
+        attempt: u32,
+    },
+    Downloading {
+        request: Request,
+        attempt: u32,
+        response: reqwest::Response,
+        total: Option<u64>,
+        downloaded: u64,
+        bytes: Vec<u8>,
+    },
+    Finished,
+}
+
+impl Request {
+    pub fn get<T: ToString>(url: T) -> Self {
+        Self {
+            method: Method::GET,
+            url: url.to_string(),
+            body: None,
+        }
+    }
+
+    pub fn post<T: ToString>(url: T, body: Box<[u8]>) -> Self {
+        Self {
+            method: Method::POST,
+            url: url.to_string(),
+            body: Some(body),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    async fn send(&self) -> Result<reqwest::Response, Error> {
+        let mut builder = CLIENT.request(self.method.clone(), &self.url);
+        if let Some(body) = &self.body {
+            builder = builder
+                .header("Content-Type", "application/octet-stream")
+                .body(body.to_vec());
+        }
+
+        let response = builder.send().await.map_err(|error| self.error(error))?;
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(Error::Status {
+                url: self.url.clone(),
+                status_code: response.status(),
+            })
+        }
+    }
+
+    fn error(&self, error: reqwest::Error) -> Error {
+        if error.is_timeout() {
+            Error::Timeout {
+                url: self.url.clone(),
+            }
+        } else {
+            Error::Connection {
+                url: self.url.clone(),
+                message: error.to_string(),
+            }
+        }
+    }
+}
+
+/// send the request and receive the response, reporting the progress.
+///
+/// Transient errors are retried with an exponential backoff, the stream never
+/// ends once the request finished or failed.
+pub fn fetch(request: Request) -> BoxStream<'static, Progress<Vec<u8>>> {
+    Box::pin(futures::stream::unfold(
+        State::Ready {
+            request,
+            attempt: 0,
+        },
+        |state| async move {
+            match state {
+                State::Ready { request, attempt } => Some(start(request, attempt).await),
+                State::Backoff { request, attempt } => {
+                    tokio::time::delay_for(BACKOFF * 2u32.pow(attempt - 1)).await;
+                    Some(start(request, attempt).await)
+                }
+                State::Downloading {
+                    request,
+                    attempt,
+                    mut response,
+                    total,
+                    downloaded,
+                    mut bytes,
+                } => match response.chunk().await {
+                    Ok(Some(chunk)) => {
+                        let downloaded = downloaded + chunk.len() as u64;
+                        bytes.extend_from_slice(&chunk);
+
+                        let progress = match total {
+                            Some(total) => {
+                                Progress::Advanced((downloaded as f32 / total as f32) * 100.0)
+                            }
+                            None => Progress::Received(downloaded),
+                        };
+
+                        Some((
+                            progress,
+                            State::Downloading {
+                                request,
+                                attempt,
+                                response,
+                                total,
+                                downloaded,
+                                bytes,
+                            },
+                        ))
+                    }
+                    Ok(None) => Some((Progress::Finished(bytes), State::Finished)),
+                    Err(error) => {
+                        let error = if error.is_timeout() {
+                            request.error(error)
+                        } else {
+                            Error::Body {
+                                url: request.url.clone(),
+                                message: error.to_string(),
+                            }
+                        };
+                        Some(retry(request, attempt, error))
+                    }
+                },
+                State::Finished => {
+                    // We do not let the stream die, as it would start a
+                    // new download repeatedly if the user is not careful
+                    // in case of errors.
+                    let _: () = futures::future::pending().await;
+
+                    None
+                }
+            }
+        },
+    ))
+}
+
+/// wait for the response outside of a subscription
+pub async fn wait<T>(mut progress: BoxStream<'static, Progress<T>>) -> Result<T, Error> {
+    while let Some(progress) = progress.next().await {
+        match progress {
+            Progress::Finished(value) => return Ok(value),
+            Progress::Failed(error) => return Err(error),
+            _ => (),
+        }
+    }
+
+    unreachable!("the request does not stop before it finished")
+}
+
+/// query the JSON document at the URL in a subscription
+pub fn query<T, U>(url: U, attempt: u32) -> iced::Subscription<Progress<T>>
+where
+    T: DeserializeOwned + Send + 'static,
+    U: ToString,
+{
+    iced::Subscription::from_recipe(Query {
+        request: Request::get(url),
+        attempt,
+        document: PhantomData,
+    })
+}
+
+/// query the JSON document at the URL outside of a subscription, waiting for
+/// the download to complete
+pub async fn get<T, U>(url: U) -> Result<T, Error>
+where
+    T: DeserializeOwned + Send + 'static,
+    U: ToString,
+{
+    wait(json(Request::get(url))).await
+}
+
+/// download the response and decode it as JSON once finished
+fn json<T>(request: Request) -> BoxStream<'static, Progress<T>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let url = request.url().to_owned();
+
+    fetch(request)
+        .map(move |progress| {
+            progress.and_then(|bytes| {
+                serde_json::from_slice(&bytes).map_err(|error| Error::Invalid {
+                    url: url.clone(),
+                    message: error.to_string(),
+                })
+            })
+        })
+        .boxed()
+}
+
+// Make sure iced can use our download stream
+impl<H, I, T> iced_native::subscription::Recipe<H, I> for Query<T>
+where
+    H: std::hash::Hasher,
+    T: DeserializeOwned + Send + 'static,
+{
+    type Output = Progress<T>;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        std::any::TypeId::of::<Self>().hash(state);
+        self.request.hash(state);
+        self.attempt.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        json(self.request)
+    }
+}
+
+/// send the request and start receiving the response
+async fn start(request: Request, attempt: u32) -> (Progress<Vec<u8>>, State) {
+    match request.send().await {
+        Ok(response) => {
+            // chunked responses do not tell their length
+            let total = response.content_length();
+            (
+                Progress::Started,
+                State::Downloading {
+                    request,
+                    attempt,
+                    response,
+                    total,
+                    downloaded: 0,
+                    bytes: Vec::with_capacity(total.unwrap_or(0) as usize),
+                },
+            )
+        }
+        Err(error) => retry(request, attempt, error),
+    }
+}
+
+/// send the request again after a delay if the error is transient, the retry
+/// being reported before the delay
+fn retry<T>(request: Request, attempt: u32, error: Error) -> (Progress<T>, State) {
+    if error.is_transient() && attempt < MAX_RETRIES {
+        let attempt = attempt + 1;
+        (
+            Progress::Retrying { attempt, error },
+            State::Backoff { request, attempt },
+        )
+    } else {
+        (Progress::Failed(error), State::Finished)
+    }
+}
+
+impl Error {
+    /// the same request may succeed later
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Connection { .. } | Error::Timeout { .. } | Error::Body { .. } => true,
+            Error::Status { status_code, .. } => {
+                status_code.is_server_error() || *status_code == StatusCode::TOO_MANY_REQUESTS
+            }
+            Error::Invalid { .. } => false,
+        }
+    }
+}
+
+impl<T> Progress<T> {
+    /// decode the response once finished
+    pub fn and_then<U, F>(self, f: F) -> Progress<U>
+    where
+        F: FnOnce(T) -> Result<U, Error>,
+    {
+        match self {
+            Progress::Started => Progress::Started,
+            Progress::Advanced(percentage) => Progress::Advanced(percentage),
+            Progress::Received(bytes) => Progress::Received(bytes),
+            Progress::Retrying { attempt, error } => Progress::Retrying { attempt, error },
+            Progress::Finished(value) => match f(value) {
+                Ok(value) => Progress::Finished(value),
+                Err(error) => Progress::Failed(error),
+            },
+            Progress::Failed(error) => Progress::Failed(error),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Connection { url, message } => {
+                write!(f, "cannot connect to {}: {}", url, message)
+            }
+            Error::Timeout { url } => write!(f, "{} did not answer in time", url),
+            Error::Status {
+                url,
+                status_code: StatusCode::NOT_FOUND,
+            } => write!(f, "{} not found", url),
+            Error::Status { url, status_code } => {
+                write!(f, "{} answered with {}", url, status_code)
+            }
+            Error::Body { url, message } => {
+                write!(f, "cannot receive the answer of {}: {}", url, message)
+            }
+            Error::Invalid { url, message } => {
+                write!(f, "invalid answer from {}: {}", url, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "http://127.0.0.1:8443/api/v0/settings";
+
+    fn status(status_code: StatusCode) -> Error {
+        Error::Status {
+            url: URL.to_owned(),
+            status_code,
+        }
+    }
+
+    #[test]
+    fn retry_transient_errors() {
+        for attempt in 0..MAX_RETRIES {
+            let error = Error::Timeout {
+                url: URL.to_owned(),
+            };
+
+            match retry::<()>(Request::get(URL), attempt, error) {
+                (
+                    Progress::Retrying {
+                        attempt: retrying, ..
+                    },
+                    State::Backoff {
+                        attempt: backoff, ..
+                    },
+                ) => {
+                    assert_eq!(retrying, attempt + 1);
+                    assert_eq!(backoff, attempt + 1);
+                }
+                (progress, _) => panic!("unexpected progress {:?}", progress),
+            }
+        }
+    }
+
+    #[test]
+    fn stop_after_the_last_retry() {
+        let error = status(StatusCode::SERVICE_UNAVAILABLE);
+
+        assert!(matches!(
+            retry::<()>(Request::get(URL), MAX_RETRIES, error),
+            (Progress::Failed(Error::Status { .. }), State::Finished)
+        ));
+    }
+
+    #[test]
+    fn fail_on_permanent_errors() {
+        assert!(matches!(
+            retry::<()>(Request::get(URL), 0, status(StatusCode::NOT_FOUND)),
+            (Progress::Failed(Error::Status { .. }), State::Finished)
+        ));
+
+        let error = Error::Invalid {
+            url: URL.to_owned(),
+            message: "expected value".to_owned(),
+        };
+        assert!(matches!(
+            retry::<()>(Request::get(URL), 0, error),
+            (Progress::Failed(Error::Invalid { .. }), State::Finished)
+        ));
+    }
+
+    #[test]
+    fn transient_errors() {
+        let url = URL.to_owned();
+
+        assert!(Error::Connection {
+            url: url.clone(),
+            message: "connection refused".to_owned(),
+        }
+        .is_transient());
+        assert!(Error::Timeout { url: url.clone() }.is_transient());
+        assert!(Error::Body {
+            url: url.clone(),
+            message: "connection reset".to_owned(),
+        }
+        .is_transient());
+        assert!(!Error::Invalid {
+            url,
+            message: "expected value".to_owned(),
+        }
+        .is_transient());
+
+        // the server errors and the rate limit may go away, not the client
+        // errors
+        assert!(status(StatusCode::INTERNAL_SERVER_ERROR).is_transient());
+        assert!(status(StatusCode::BAD_GATEWAY).is_transient());
+        assert!(status(StatusCode::SERVICE_UNAVAILABLE).is_transient());
+        assert!(status(StatusCode::TOO_MANY_REQUESTS).is_transient());
+        assert!(!status(StatusCode::BAD_REQUEST).is_transient());
+        assert!(!status(StatusCode::NOT_FOUND).is_transient());
+        assert!(!status(StatusCode::UNAUTHORIZED).is_transient());
+    }
+}
@@ -0,0 +1,291 @@
+use chain_addr::Discrimination;
+use chain_impl_mockchain::fee::LinearFee;
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+use std::{fmt, num::NonZeroU64};
+use wallet_core as chain;
+
+use crate::{block0, node_client};
+
+/// the settings of the blockchain as currently applied by the node, the fees
+/// may have been updated since the block0
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeSettings {
+    pub block0_hash: String,
+    pub fees: Fees,
+    /// not given by the older nodes
+    #[serde(default)]
+    pub discrimination: Option<NodeDiscrimination>,
+    /// when the node's current slot started, not given by the older nodes
+    #[serde(default)]
+    pub curr_slot_start_time: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fees {
+    pub constant: u64,
+    pub coefficient: u64,
+    pub certificate: u64,
+    #[serde(default)]
+    pub per_certificate_fees: PerCertificateFees,
+    #[serde(default)]
+    pub per_vote_certificate_fees: PerVoteCertificateFees,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PerCertificateFees {
+    #[serde(default)]
+    pub certificate_pool_registration: Option<NonZeroU64>,
+    #[serde(default)]
+    pub certificate_stake_delegation: Option<NonZeroU64>,
+    #[serde(default)]
+    pub certificate_owner_stake_delegation: Option<NonZeroU64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PerVoteCertificateFees {
+    #[serde(default)]
+    pub certificate_vote_plan: Option<NonZeroU64>,
+    #[serde(default)]
+    pub certificate_vote_cast: Option<NonZeroU64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeDiscrimination {
+    Production,
+    Test,
+}
+
+pub type Progress = node_client::Progress<NodeSettings>;
+
+#[derive(Debug)]
+pub enum Error {
+    Download(node_client::Error),
+    /// the node does not run the blockchain of the block0
+    Block0(block0::Error),
+    /// the node does not give when its current slot started
+    NoTime,
+}
+
+/// query the settings of the node, checking it runs the blockchain of the
+/// given block0
+pub async fn get<T: ToString>(url: T, block0: &[u8]) -> Result<NodeSettings, Error> {
+    let settings: NodeSettings = node_client::get(url).await.map_err(Error::Download)?;
+    settings.verify(block0)?;
+
+    Ok(settings)
+}
+
+impl NodeSettings {
+    /// check the node runs the blockchain of the block0
+    pub fn verify(&self, block0: &[u8]) -> Result<(), Error> {
+        block0::verify(block0, &self.block0_hash).map_err(Error::Block0)
+    }
+
+    /// use the node's fees and discrimination in the settings read from the
+    /// block0, returning what changed
+    pub fn apply(&self, settings: &mut chain::Settings) -> Vec<String> {
+        self.apply_to(&mut settings.fees, &mut settings.discrimination)
+    }
+
+    fn apply_to(&self, fees: &mut LinearFee, discrimination: &mut Discrimination) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut change = |name: &str, old: u64, new: u64| {
+            if old != new {
+                changes.push(format!("{}: {} -> {} lovelace", name, old, new));
+            }
+        };
+        let get = |fee: Option<NonZeroU64>| fee.map(NonZeroU64::get).unwrap_or(0);
+
+        change("constant fee", fees.constant, self.fees.constant);
+        change(
+            "fee per input and output",
+            fees.coefficient,
+            self.fees.coefficient,
+        );
+        change("certificate fee", fees.certificate, self.fees.certificate);
+        change(
+            "vote fee",
+            get(fees.per_vote_certificate_fees.certificate_vote_cast),
+            get(self.fees.per_vote_certificate_fees.certificate_vote_cast),
+        );
+        change(
+            "vote plan fee",
+            get(fees.per_vote_certificate_fees.certificate_vote_plan),
+            get(self.fees.per_vote_certificate_fees.certificate_vote_plan),
+        );
+
+        fees.constant = self.fees.constant;
+        fees.coefficient = self.fees.coefficient;
+        fees.certificate = self.fees.certificate;
+        let per_certificate = &self.fees.per_certificate_fees;
+        fees.per_certificate_fees.certificate_pool_registration =
+            per_certificate.certificate_pool_registration;
+        fees.per_certificate_fees.certificate_stake_delegation =
+            per_certificate.certificate_stake_delegation;
+        fees.per_certificate_fees.certificate_owner_stake_delegation =
+            per_certificate.certificate_owner_stake_delegation;
+        let per_vote_certificate = &self.fees.per_vote_certificate_fees;
+        fees.per_vote_certificate_fees.certificate_vote_plan =
+            per_vote_certificate.certificate_vote_plan;
+        fees.per_vote_certificate_fees.certificate_vote_cast =
+            per_vote_certificate.certificate_vote_cast;
+
+        if let Some(node_discrimination) = self.discrimination {
+            let node_discrimination = match node_discrimination {
+                NodeDiscrimination::Production => Discrimination::Production,
+                NodeDiscrimination::Test => Discrimination::Test,
+            };
+            if *discrimination != node_discrimination {
+                changes.push(format!(
+                    "discrimination: {:?} -> {:?}",
+                    discrimination, node_discrimination
+                ));
+                *discrimination = node_discrimination;
+            }
+        }
+
+        changes
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Download(error) => write!(f, "cannot get the settings of the node: {}", error),
+            Error::Block0(error) => write!(f, "the node runs another blockchain: {}", error),
+            Error::NoTime => write!(f, "the node does not give its time"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Download(error) => Some(error),
+            Error::Block0(error) => Some(error),
+            Error::NoTime => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: &str = r#"{
+        "block0Hash": "8f3a3e4f2b25fd6ff1dcb1ffd5e48fdc5a3a3e0ac1e8d3d2b3f2c1e0d9b8a7f6",
+        "block0Time": "2020-07-10T12:00:00+00:00",
+        "currSlotStartTime": "2020-07-10T14:30:20+00:00",
+        "consensusVersion": "bft",
+        "fees": {
+            "constant": 20,
+            "coefficient": 2,
+            "certificate": 100,
+            "per_certificate_fees": {
+                "certificate_pool_registration": 500
+            },
+            "per_vote_certificate_fees": {
+                "certificate_vote_plan": 300,
+                "certificate_vote_cast": 10
+            }
+        },
+        "discrimination": "test",
+        "maxTxsPerBlock": 100,
+        "slotDuration": 20,
+        "slotsPerEpoch": 180
+    }"#;
+
+    fn settings(json: &str) -> NodeSettings {
+        serde_json::from_str(json).unwrap()
+    }
+
+    /// the fees of a block0 without certificate fees
+    fn block0_fees() -> LinearFee {
+        LinearFee::new(10, 2, 100)
+    }
+
+    #[test]
+    fn read_the_settings() {
+        let settings = settings(SETTINGS);
+
+        assert_eq!(settings.fees.constant, 20);
+        assert_eq!(settings.discrimination, Some(NodeDiscrimination::Test));
+        assert_eq!(
+            settings.curr_slot_start_time.map(|time| time.to_rfc3339()),
+            Some("2020-07-10T14:30:20+00:00".to_owned())
+        );
+
+        // older nodes give neither the discrimination nor the time
+        let settings = self::settings(
+            r#"{
+                "block0Hash": "8f3a",
+                "fees": { "constant": 20, "coefficient": 2, "certificate": 100 }
+            }"#,
+        );
+        assert_eq!(settings.discrimination, None);
+        assert!(settings.curr_slot_start_time.is_none());
+        assert!(settings
+            .fees
+            .per_vote_certificate_fees
+            .certificate_vote_cast
+            .is_none());
+    }
+
+    #[test]
+    fn reject_invalid_settings() {
+        let parse = |json: &str| serde_json::from_str::<NodeSettings>(json);
+
+        assert!(parse("").is_err());
+        assert!(parse("{}").is_err());
+        assert!(parse(r#"{ "block0Hash": "8f3a" }"#).is_err());
+        assert!(parse(&SETTINGS.replace(r#""test""#, r#""staging""#)).is_err());
+        assert!(parse(&SETTINGS.replace("2020-07-10T14:30:20+00:00", "14:30:20")).is_err());
+    }
+
+    #[test]
+    fn apply_the_settings() {
+        let mut fees = block0_fees();
+        let mut discrimination = Discrimination::Production;
+
+        let changes = settings(SETTINGS).apply_to(&mut fees, &mut discrimination);
+        assert_eq!(
+            changes,
+            vec![
+                "constant fee: 10 -> 20 lovelace",
+                "vote fee: 0 -> 10 lovelace",
+                "vote plan fee: 0 -> 300 lovelace",
+                "discrimination: Production -> Test",
+            ]
+        );
+        assert_eq!(fees.constant, 20);
+        assert_eq!(fees.coefficient, 2);
+        assert_eq!(
+            fees.per_certificate_fees.certificate_pool_registration,
+            NonZeroU64::new(500)
+        );
+        assert_eq!(
+            fees.per_vote_certificate_fees.certificate_vote_cast,
+            NonZeroU64::new(10)
+        );
+        assert_eq!(discrimination, Discrimination::Test);
+
+        // the settings already match the node's
+        assert!(settings(SETTINGS)
+            .apply_to(&mut fees, &mut discrimination)
+            .is_empty());
+    }
+
+    #[test]
+    fn keep_the_discrimination_not_given() {
+        let mut fees = block0_fees();
+        let mut discrimination = Discrimination::Production;
+        let settings = settings(&SETTINGS.replace(r#""discrimination": "test","#, ""));
+
+        let changes = settings.apply_to(&mut fees, &mut discrimination);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(discrimination, Discrimination::Production);
+    }
+}
@@ -0,0 +1,198 @@
+use bech32::FromBase32 as _;
+use cryptoxide::{digest::Digest as _, sha2::Sha512};
+use std::fmt;
+
+/// human readable part of the bech32 ed25519 secret keys
+const ED25519_HRP: &str = "ed25519_sk";
+/// human readable part of the bech32 ed25519 extended secret keys
+const ED25519_EXTENDED_HRP: &str = "ed25519e_sk";
+
+const ED25519_SIZE: usize = 32;
+const ED25519_EXTENDED_SIZE: usize = 64;
+
+/// an ed25519 extended secret key, the format of the wallet's account keys
+pub type SecretKey = [u8; ED25519_EXTENDED_SIZE];
+
+#[derive(Debug)]
+pub enum Error {
+    Bech32(bech32::Error),
+    UnexpectedHrp(String),
+    Hex(hex::FromHexError),
+    InvalidSize(usize),
+    InvalidExtendedKey,
+}
+
+/// tell if the input is meant to be a secret key rather than mnemonics: a key
+/// is a single bech32 ed25519 key or a long hex string, while a mnemonic word
+/// being typed is a single word too
+pub fn is_secret_key(input: &str) -> bool {
+    let input = input.trim();
+    if input.is_empty() || input.contains(char::is_whitespace) {
+        return false;
+    }
+
+    input.starts_with("ed25519")
+        || (input.len() >= 2 * ED25519_SIZE && input.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// parse an ed25519 or ed25519 extended secret key, either bech32 or hex
+/// encoded.
+///
+/// Normal ed25519 keys are expanded to their extended form.
+pub fn parse(input: &str) -> Result<SecretKey, Error> {
+    let input = input.trim();
+
+    let bytes = if input.starts_with(ED25519_EXTENDED_HRP) || input.starts_with(ED25519_HRP) {
+        let (hrp, data) = bech32::decode(input).map_err(Error::Bech32)?;
+        let bytes = Vec::<u8>::from_base32(&data).map_err(Error::Bech32)?;
+
+        let expected_size = match hrp.as_str() {
+            ED25519_HRP => ED25519_SIZE,
+            ED25519_EXTENDED_HRP => ED25519_EXTENDED_SIZE,
+            _ => return Err(Error::UnexpectedHrp(hrp)),
+        };
+        if bytes.len() != expected_size {
+            return Err(Error::InvalidSize(bytes.len()));
+        }
+        bytes
+    } else {
+        hex::decode(input).map_err(Error::Hex)?
+    };
+
+    match bytes.len() {
+        ED25519_SIZE => Ok(extend(&bytes)),
+        ED25519_EXTENDED_SIZE => {
+            let mut key = [0; ED25519_EXTENDED_SIZE];
+            key.copy_from_slice(&bytes);
+            if is_extended(&key) {
+                Ok(key)
+            } else {
+                Err(Error::InvalidExtendedKey)
+            }
+        }
+        size => Err(Error::InvalidSize(size)),
+    }
+}
+
+/// expand an ed25519 secret key the way ed25519 does before signing
+fn extend(seed: &[u8]) -> SecretKey {
+    let mut key = [0; ED25519_EXTENDED_SIZE];
+
+    let mut hasher = Sha512::new();
+    hasher.input(seed);
+    hasher.result(&mut key);
+
+    key[0] &= 0b1111_1000;
+    key[31] &= 0b0011_1111;
+    key[31] |= 0b0100_0000;
+
+    key
+}
+
+/// check the scalar part of the key has been clamped
+fn is_extended(key: &SecretKey) -> bool {
+    (key[0] & 0b0000_0111) == 0 && (key[31] & 0b1100_0000) == 0b0100_0000
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Bech32(error) => write!(f, "invalid bech32 secret key: {}", error),
+            Error::UnexpectedHrp(hrp) => write!(
+                f,
+                "unexpected secret key type '{}', expected {} or {}",
+                hrp, ED25519_HRP, ED25519_EXTENDED_HRP
+            ),
+            Error::Hex(error) => write!(f, "invalid hex secret key: {}", error),
+            Error::InvalidSize(size) => write!(
+                f,
+                "invalid secret key size of {} bytes, expected {} or {}",
+                size, ED25519_SIZE, ED25519_EXTENDED_SIZE
+            ),
+            Error::InvalidExtendedKey => write!(f, "invalid ed25519 extended secret key"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bech32::ToBase32 as _;
+
+    const SEED: [u8; ED25519_SIZE] = [7; ED25519_SIZE];
+
+    fn bech32(hrp: &str, bytes: &[u8]) -> String {
+        bech32::encode(hrp, bytes.to_base32()).unwrap()
+    }
+
+    #[test]
+    fn tell_keys_from_mnemonics() {
+        assert!(is_secret_key(&bech32(ED25519_HRP, &SEED)));
+        assert!(is_secret_key(&format!(" {} ", hex::encode(SEED))));
+
+        assert!(!is_secret_key(""));
+        assert!(!is_secret_key("abandon"));
+        assert!(!is_secret_key("abandon abandon about"));
+        assert!(!is_secret_key(&hex::encode(&SEED[..16])));
+    }
+
+    #[test]
+    fn extend_ed25519_keys() {
+        let key = parse(&hex::encode(SEED)).unwrap();
+
+        assert!(is_extended(&key));
+        assert_eq!(key.to_vec(), extend(&SEED).to_vec());
+        assert_eq!(
+            parse(&bech32(ED25519_HRP, &SEED)).unwrap().to_vec(),
+            key.to_vec()
+        );
+    }
+
+    #[test]
+    fn keep_extended_keys() {
+        let key = extend(&SEED);
+
+        assert_eq!(
+            parse(&hex::encode(&key[..])).unwrap().to_vec(),
+            key.to_vec()
+        );
+        assert_eq!(
+            parse(&bech32(ED25519_EXTENDED_HRP, &key)).unwrap().to_vec(),
+            key.to_vec()
+        );
+    }
+
+    #[test]
+    fn reject_unclamped_extended_keys() {
+        let mut key = extend(&SEED);
+        key[0] |= 0b0000_0001;
+
+        assert!(matches!(
+            parse(&hex::encode(&key[..])),
+            Err(Error::InvalidExtendedKey)
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_keys() {
+        assert!(matches!(
+            parse(&hex::encode(&SEED[..31])),
+            Err(Error::InvalidSize(31))
+        ));
+        assert!(matches!(
+            parse(&bech32(ED25519_HRP, &extend(&SEED))),
+            Err(Error::InvalidSize(64))
+        ));
+        assert!(matches!(
+            parse(&bech32("ed25519_skx", &SEED)),
+            Err(Error::UnexpectedHrp(hrp)) if hrp == "ed25519_skx"
+        ));
+        assert!(matches!(parse("not a key"), Err(Error::Hex(_))));
+
+        let mut key = bech32(ED25519_HRP, &SEED);
+        key.pop();
+        assert!(matches!(parse(&key), Err(Error::Bech32(_))));
+    }
+}
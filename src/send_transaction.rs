@@ -1,16 +1,22 @@
-use iced_futures::futures;
+use iced_futures::futures::{self, StreamExt as _};
+
+use crate::node_client::{self, Request};
+
+/// the progress of the upload, finished with the id of the fragment
+pub type Progress = node_client::Progress<String>;
 
 // Just a little utility function
-pub fn post<T: ToString>(url: T, body: Box<[u8]>) -> iced::Subscription<Progress> {
+pub fn post<T: ToString>(url: T, body: Box<[u8]>, attempt: u32) -> iced::Subscription<Progress> {
     iced::Subscription::from_recipe(Download {
-        url: url.to_string(),
-        body,
+        request: Request::post(url, body),
+        attempt,
     })
 }
 
 pub struct Download {
-    url: String,
-    body: Box<[u8]>,
+    request: Request,
+    /// a new attempt sends the fragment again
+    attempt: u32,
 }
 
 // Make sure iced can use our download stream
@@ -24,136 +30,26 @@ where
         use std::hash::Hash;
 
         std::any::TypeId::of::<Self>().hash(state);
-        self.url.hash(state);
+        self.request.hash(state);
+        self.attempt.hash(state);
     }
 
     fn stream(
         self: Box<Self>,
         _input: futures::stream::BoxStream<'static, I>,
     ) -> futures::stream::BoxStream<'static, Self::Output> {
-        Box::pin(futures::stream::unfold(
-            State::Ready(self.url, self.body),
-            |state| async move {
-                match state {
-                    State::Ready(url, body) => {
-                        let client = reqwest::Client::new();
-
-                        let tx = hex::encode(&body);
-                        dbg!(&tx);
-                        let response = client
-                            .post(&url)
-                            .header("Content-Type", "application/octet-stream")
-                            .body(body.to_vec())
-                            .send()
-                            .await;
-
-                        match response {
-                            Ok(response) => {
-                                if let Some(total) = response.content_length() {
-                                    Some((
-                                        Progress::Started,
-                                        State::Downloading {
-                                            response,
-                                            total,
-                                            downloaded: 0,
-                                            bytes: Vec::with_capacity(total as usize),
-                                        },
-                                    ))
-                                } else {
-                                    Some((
-                                        Progress::Errored {
-                                            status_code: response.status(),
-                                        },
-                                        State::Finished,
-                                    ))
-                                }
-                            }
-                            Err(error) => Some((
-                                Progress::Failure {
-                                    error: error.to_string(),
-                                },
-                                State::Finished,
-                            )),
-                        }
-                    }
-                    State::Downloading {
-                        mut response,
-                        total,
-                        downloaded,
-                        mut bytes,
-                    } => {
-                        dbg!(response.status());
-                        let status = response.status();
-                        if status != reqwest::StatusCode::OK {
-                            dbg!(response.text().await);
-                            return Some((
-                                Progress::Errored {
-                                    status_code: status,
-                                },
-                                State::Finished,
-                            ));
-                        }
-                        match response.chunk().await {
-                            Ok(Some(chunk)) => {
-                                let downloaded = downloaded + chunk.len() as u64;
-
-                                let percentage = (downloaded as f32 / total as f32) * 100.0;
-
-                                bytes.extend_from_slice(&chunk);
-
-                                Some((
-                                    Progress::Advanced(percentage),
-                                    State::Downloading {
-                                        response,
-                                        total,
-                                        downloaded,
-                                        bytes,
-                                    },
-                                ))
-                            }
-                            Ok(None) => {
-                                let id = String::from_utf8_lossy(&bytes).to_string();
-                                Some((Progress::Finished { id }, State::Finished))
-                            }
-                            Err(error) => Some((
-                                Progress::Failure {
-                                    error: error.to_string(),
-                                },
-                                State::Finished,
-                            )),
-                        }
-                    }
-                    State::Finished => {
-                        dbg!();
-                        // We do not let the stream die, as it would start a
-                        // new download repeatedly if the user is not careful
-                        // in case of errors.
-                        let _: () = iced::futures::future::pending().await;
-
-                        None
-                    }
-                }
-            },
-        ))
+        upload(self.request)
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum Progress {
-    Started,
-    Advanced(f32),
-    Finished { id: String },
-    Errored { status_code: reqwest::StatusCode },
-    Failure { error: String },
+/// send the fragment outside of a subscription, waiting for the node to
+/// answer with the fragment id
+pub async fn send<T: ToString>(url: T, body: Box<[u8]>) -> Result<String, node_client::Error> {
+    node_client::wait(upload(Request::post(url, body))).await
 }
 
-pub enum State {
-    Ready(String, Box<[u8]>),
-    Downloading {
-        response: reqwest::Response,
-        total: u64,
-        downloaded: u64,
-        bytes: Vec<u8>,
-    },
-    Finished,
+fn upload(request: Request) -> futures::stream::BoxStream<'static, Progress> {
+    node_client::fetch(request)
+        .map(|progress| progress.and_then(|bytes| Ok(String::from_utf8_lossy(&bytes).to_string())))
+        .boxed()
 }
@@ -0,0 +1,39 @@
+use serde::Deserialize;
+use std::{fmt, ops::Range};
+
+use crate::{config::PayloadType, node_client};
+
+pub type Progress = node_client::Progress<Vec<VotePlanStatus>>;
+
+/// a vote plan active on the node
+#[derive(Debug, Clone, Deserialize)]
+pub struct VotePlanStatus {
+    pub id: String,
+    pub payload: PayloadType,
+    pub vote_start: BlockDate,
+    pub vote_end: BlockDate,
+    pub committee_end: BlockDate,
+    pub proposals: Vec<ProposalStatus>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProposalStatus {
+    pub index: u8,
+    /// hash of the proposal's external description
+    pub proposal_id: String,
+    pub options: Range<u8>,
+    #[serde(default)]
+    pub votes_cast: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+pub struct BlockDate {
+    pub epoch: u32,
+    pub slot_id: u32,
+}
+
+impl fmt::Display for BlockDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.epoch, self.slot_id)
+    }
+}
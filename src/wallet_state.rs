@@ -1,160 +1,165 @@
-use iced_futures::futures;
 use serde::Deserialize;
+use std::{collections::BTreeMap, fmt};
 
-// Just a little utility function
-pub fn query<T: ToString>(url: T) -> iced::Subscription<Progress> {
-    iced::Subscription::from_recipe(Download {
-        url: url.to_string(),
-    })
-}
+use crate::node_client;
 
-pub struct Download {
-    url: String,
-}
+pub type Progress = node_client::Progress<AccountState>;
 
+/// the state of an account as returned by the node
 #[derive(Debug, Clone, Deserialize)]
 pub struct AccountState {
     pub value: u64,
     pub counter: u32,
+    #[serde(default)]
+    pub delegation: Delegation,
+    /// the rewards of the last epoch the account was rewarded in
+    #[serde(default)]
+    pub last_rewards: Option<LastRewards>,
+    /// the fields this version does not know about, kept as they are
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
 }
 
-// Make sure iced can use our download stream
-impl<H, I> iced_native::subscription::Recipe<H, I> for Download
-where
-    H: std::hash::Hasher,
-{
-    type Output = Progress;
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Delegation {
+    /// the stake pools the account delegates to, with their share of the
+    /// account's stake
+    Pools { pools: Vec<(String, u8)> },
+    /// a delegation format this version does not know about
+    Other(serde_json::Value),
+}
 
-    fn hash(&self, state: &mut H) {
-        use std::hash::Hash;
+#[derive(Debug, Clone, Deserialize)]
+pub struct LastRewards {
+    pub epoch: u32,
+    /// in lovelace
+    pub reward: u64,
+}
 
-        std::any::TypeId::of::<Self>().hash(state);
-        self.url.hash(state);
+impl Default for Delegation {
+    fn default() -> Self {
+        Delegation::Pools { pools: Vec::new() }
     }
+}
 
-    fn stream(
-        self: Box<Self>,
-        _input: futures::stream::BoxStream<'static, I>,
-    ) -> futures::stream::BoxStream<'static, Self::Output> {
-        Box::pin(futures::stream::unfold(
-            State::Ready(self.url),
-            |state| async move {
-                match state {
-                    State::Ready(url) => {
-                        let response = reqwest::get(&url).await;
-
-                        match response {
-                            Ok(response) => {
-                                if let Some(total) = response.content_length() {
-                                    Some((
-                                        Progress::Started,
-                                        State::Downloading {
-                                            response,
-                                            total,
-                                            downloaded: 0,
-                                            bytes: Vec::with_capacity(total as usize),
-                                        },
-                                    ))
-                                } else {
-                                    Some((
-                                        Progress::Errored {
-                                            status_code: response.status(),
-                                        },
-                                        State::Finished,
-                                    ))
-                                }
-                            }
-                            Err(error) => Some((
-                                Progress::Failure {
-                                    error: error.to_string(),
-                                },
-                                State::Finished,
-                            )),
-                        }
-                    }
-                    State::Downloading {
-                        mut response,
-                        total,
-                        downloaded,
-                        mut bytes,
-                    } => {
-                        dbg!(response.status());
-                        if response.status() != reqwest::StatusCode::OK {
-                            return Some((
-                                Progress::Errored {
-                                    status_code: response.status(),
-                                },
-                                State::Finished,
-                            ));
-                        }
-                        match response.chunk().await {
-                            Ok(Some(chunk)) => {
-                                let downloaded = downloaded + chunk.len() as u64;
-
-                                let percentage = (downloaded as f32 / total as f32) * 100.0;
-
-                                bytes.extend_from_slice(&chunk);
-
-                                Some((
-                                    Progress::Advanced(percentage),
-                                    State::Downloading {
-                                        response,
-                                        total,
-                                        downloaded,
-                                        bytes,
-                                    },
-                                ))
-                            }
-                            Ok(None) => match serde_json::from_slice::<AccountState>(&bytes) {
-                                Err(error) => Some((
-                                    Progress::Failure {
-                                        error: error.to_string(),
-                                    },
-                                    State::Finished,
-                                )),
-                                Ok(account_state) => {
-                                    Some((Progress::Finished { account_state }, State::Finished))
-                                }
-                            },
-                            Err(error) => Some((
-                                Progress::Failure {
-                                    error: error.to_string(),
-                                },
-                                State::Finished,
-                            )),
-                        }
-                    }
-                    State::Finished => {
-                        dbg!();
-                        // We do not let the stream die, as it would start a
-                        // new download repeatedly if the user is not careful
-                        // in case of errors.
-                        let _: () = iced::futures::future::pending().await;
-
-                        None
+impl fmt::Display for Delegation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Delegation::Pools { pools } if pools.is_empty() => write!(f, "not delegated"),
+            Delegation::Pools { pools } => {
+                let total: u32 = pools.iter().map(|(_, parts)| *parts as u32).sum();
+                for (index, (pool, parts)) in pools.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
                     }
+                    write!(f, "{} ({}/{})", pool, parts, total)?;
                 }
-            },
-        ))
+                Ok(())
+            }
+            Delegation::Other(value) => write!(f, "{}", value),
+        }
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum Progress {
-    Started,
-    Advanced(f32),
-    Finished { account_state: AccountState },
-    Errored { status_code: reqwest::StatusCode },
-    Failure { error: String },
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL: &str = "2e6d2a2e5f15b2b7a4f5c24b9e6a7f0cc4b1b5e0d3d1c6f7a8b9c0d1e2f3a4b5";
+
+    fn parse(json: &str) -> AccountState {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn parse_delegated_accounts() {
+        let state = parse(&format!(
+            r#"{{
+                "delegation": {{ "pools": [["{}", 1], ["{}", 3]] }},
+                "value": 1000000,
+                "counter": 2,
+                "last_rewards": {{ "epoch": 12, "reward": 345 }}
+            }}"#,
+            POOL, POOL
+        ));
 
-pub enum State {
-    Ready(String),
-    Downloading {
-        response: reqwest::Response,
-        total: u64,
-        downloaded: u64,
-        bytes: Vec<u8>,
-    },
-    Finished,
+        assert_eq!(state.value, 1_000_000);
+        assert_eq!(state.counter, 2);
+        match &state.delegation {
+            Delegation::Pools { pools } => {
+                assert_eq!(pools, &vec![(POOL.to_owned(), 1), (POOL.to_owned(), 3)])
+            }
+            delegation => panic!("unexpected delegation {:?}", delegation),
+        }
+        assert_eq!(
+            state.delegation.to_string(),
+            format!("{} (1/4), {} (3/4)", POOL, POOL)
+        );
+        let rewards = state.last_rewards.unwrap();
+        assert_eq!((rewards.epoch, rewards.reward), (12, 345));
+        assert!(state.other.is_empty());
+    }
+
+    #[test]
+    fn parse_accounts_not_delegated() {
+        let state = parse(r#"{ "delegation": { "pools": [] }, "value": 10, "counter": 0 }"#);
+        assert_eq!(state.delegation.to_string(), "not delegated");
+        assert!(state.last_rewards.is_none());
+
+        // older nodes do not give the delegation nor the rewards
+        let state = parse(r#"{ "value": 10, "counter": 0 }"#);
+        assert_eq!(state.delegation.to_string(), "not delegated");
+        assert!(state.last_rewards.is_none());
+    }
+
+    #[test]
+    fn keep_unknown_fields() {
+        let state = parse(
+            r#"{
+                "value": 10,
+                "counter": 0,
+                "tokens": {},
+                "last_updated": "2020-07-10T12:00:00+00:00"
+            }"#,
+        );
+
+        assert_eq!(state.other.len(), 2);
+        assert_eq!(state.other["tokens"], serde_json::json!({}));
+        assert_eq!(
+            state.other["last_updated"],
+            serde_json::json!("2020-07-10T12:00:00+00:00")
+        );
+    }
+
+    #[test]
+    fn keep_unknown_delegations() {
+        let state = parse(
+            r#"{
+                "delegation": { "ratio": { "parts": 2, "pools": 1 } },
+                "value": 10,
+                "counter": 0
+            }"#,
+        );
+
+        match &state.delegation {
+            Delegation::Other(value) => {
+                assert_eq!(value["ratio"]["parts"], serde_json::json!(2))
+            }
+            delegation => panic!("unexpected delegation {:?}", delegation),
+        }
+        assert_eq!(
+            state.delegation.to_string(),
+            r#"{"ratio":{"parts":2,"pools":1}}"#
+        );
+    }
+
+    #[test]
+    fn reject_invalid_states() {
+        let parse = |json: &str| serde_json::from_str::<AccountState>(json);
+
+        assert!(parse("{}").is_err());
+        assert!(parse(r#"{ "value": 10 }"#).is_err());
+        assert!(parse(r#"{ "value": -1, "counter": 0 }"#).is_err());
+    }
 }